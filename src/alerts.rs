@@ -0,0 +1,481 @@
+use std::collections::HashMap;
+use std::fs;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use reqwest::blocking::Response;
+use reqwest::Error as ReqwestError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::credentials::ApiKey;
+use crate::error::ShodanError;
+use crate::helpers::{self, RateLimiter};
+use crate::models::{Alert, AlertTrigger};
+use crate::shodan;
+
+/*
+    Description:
+        Portable description of every network alert of an account, as written by export_alert_config().
+        Alert and notifier IDs are account specific, so the document only relies on names and notifier descriptions.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertConfig {
+    pub version: u32,
+    pub alerts: Vec<ExportedAlert>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedAlert {
+    pub name: String,
+    pub ips: Vec<String>,
+    #[serde(default)]
+    pub expires: i32,
+    #[serde(default)]
+    pub triggers: Vec<ExportedTrigger>,
+    #[serde(default)]
+    pub notifiers: Vec<ExportedNotifier>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedTrigger {
+    pub name: AlertTrigger,
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedNotifier {
+    pub id: String,
+    pub provider: String,
+    pub description: Option<String>,
+}
+
+/*
+    Description:
+        What import_alert_config() did for one exported alert.
+        skipped_notifiers lists the notifier descriptions that could not be found on the target account, or that match
+        several of its notifiers.
+*/
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportedAlert {
+    pub name: String,
+    pub id: String,
+    pub skipped_notifiers: Vec<String>,
+}
+
+/*
+    Description:
+        Outcome of one service of a bulk whitelist operation (bulk_add_whitelist() / bulk_delete_whitelist()).
+        error holds the reason when success is false.
+*/
+#[derive(Debug, Clone, Serialize)]
+pub struct WhitelistResult {
+    pub service: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+const ALERT_CONFIG_VERSION: u32 = 1;
+
+pub fn export_alert_config(api_key: &ApiKey) -> Result<AlertConfig, ShodanError> {
+    /*
+        Description:
+            Export the full alert configuration of an account
+            Walks every alert returned by (GET /shodan/alert/info) and fetches its details with (GET /shodan/alert/{id}/info),
+            keeping the name, the monitored IPs, the enabled triggers with their ignored services and the attached notifiers.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+        Returns:
+            Result<AlertConfig, ShodanError>:
+                Ok(AlertConfig): Returns the portable alert configuration.
+                Err(ShodanError): Returns an error if one of the requests fails or returns an unexpected body.
+    */
+    let mut alerts = Vec::new();
+    for summary in shodan::get_alert_info(api_key)? {
+        let alert = shodan::get_alert_info_id(&summary.id, api_key)?;
+        alerts.push(exported_alert_from(&alert));
+    }
+
+    Ok(AlertConfig { version: ALERT_CONFIG_VERSION, alerts })
+}
+
+pub fn import_alert_config(api_key: &ApiKey, config: &AlertConfig) -> Result<Vec<ImportedAlert>, ShodanError> {
+    /*
+        Description:
+            Import an alert configuration
+            Every trigger of the document is first checked with validate_triggers(), then every alert is recreated on the
+            account linked to api_key: creates the alert, enables its triggers, ignores the whitelisted services and attaches
+            the notifiers. Notifier IDs are remapped by description against the notifiers of the target account (GET /notifier),
+            the "default" notifier is kept as is. A description shared by several notifiers of the target account is skipped.
+            WARNING ! -> Alerts are created one after the other, if a request fails the alerts created before it are kept.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key of the target account
+            config (&AlertConfig): Configuration previously returned by export_alert_config() or read_alert_config()
+        Returns:
+            Result<Vec<ImportedAlert>, ShodanError>:
+                Ok(Vec<ImportedAlert>): Returns the new alert ID and the skipped notifiers for every imported alert.
+                Err(ShodanError): Returns an error if the document version is unknown, a trigger is not available or one of the requests fails.
+    */
+    if config.version != ALERT_CONFIG_VERSION {
+        return Err(ShodanError::Invalid(format!("unsupported alert configuration version {}", config.version)));
+    }
+
+    let triggers: Vec<AlertTrigger> = config
+        .alerts
+        .iter()
+        .flat_map(|alert| alert.triggers.iter().map(|trigger| trigger.name.clone()))
+        .collect();
+    validate_triggers(api_key, &triggers)?;
+
+    let notifier_ids = notifier_ids_by_description(api_key)?;
+
+    config
+        .alerts
+        .iter()
+        .map(|alert| {
+            recreate_alert(api_key, alert, |notifier| {
+                if notifier.id == "default" {
+                    Some(notifier.id.clone())
+                } else {
+                    notifier
+                        .description
+                        .as_ref()
+                        .and_then(|description| notifier_ids.get(description).cloned())
+                }
+            })
+        })
+        .collect()
+}
+
+pub fn write_alert_config(path: &str, config: &AlertConfig) -> Result<(), ShodanError> {
+    /*
+        Description:
+            Writes an alert configuration to a file as pretty-printed JSON.
+        Parameters:
+            path (&str): The path of the file to write.
+            config (&AlertConfig): The configuration to write.
+        Returns:
+            Result<(), ShodanError>:
+                Ok(()): The file was written.
+                Err(ShodanError): Returns an error if encoding or writing the file fails.
+    */
+    let content = serde_json::to_string_pretty(config)?;
+    fs::write(path, content)?;
+    Ok(())
+}
+
+pub fn read_alert_config(path: &str) -> Result<AlertConfig, ShodanError> {
+    /*
+        Description:
+            Reads an alert configuration previously written by write_alert_config().
+        Parameters:
+            path (&str): The path of the file to read.
+        Returns:
+            Result<AlertConfig, ShodanError>:
+                Ok(AlertConfig): Returns the decoded configuration.
+                Err(ShodanError): Returns an error if reading or decoding the file fails.
+    */
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub fn get_available_triggers(api_key: &ApiKey) -> Result<Vec<AlertTrigger>, ShodanError> {
+    /*
+        Description:
+            Returns the triggers that can currently be enabled on network alerts, decoded from (GET /shodan/alert/triggers).
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+        Returns:
+            Result<Vec<AlertTrigger>, ShodanError>:
+                Ok(Vec<AlertTrigger>): Returns the available triggers.
+                Err(ShodanError): Returns an error if the request fails or returns an unexpected body.
+    */
+    let triggers = helpers::json_from_response(shodan::get_alert_triggers(api_key))?;
+    let triggers = triggers
+        .as_array()
+        .ok_or_else(|| ShodanError::Invalid("trigger listing is not an array".to_string()))?;
+
+    Ok(triggers
+        .iter()
+        .filter_map(|trigger| trigger.get("name").and_then(Value::as_str))
+        .map(AlertTrigger::from)
+        .collect())
+}
+
+pub fn validate_triggers(api_key: &ApiKey, triggers: &[AlertTrigger]) -> Result<(), ShodanError> {
+    /*
+        Description:
+            Checks a set of triggers against the triggers Shodan reports at runtime (get_available_triggers()),
+            so a typo in an Other(String) trigger or a trigger removed by Shodan is caught before touching any alert.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            triggers (&[AlertTrigger]): The triggers to check.
+        Returns:
+            Result<(), ShodanError>:
+                Ok(()): Every trigger is available.
+                Err(ShodanError): Returns ShodanError::Invalid naming the unknown triggers, or the error of the request.
+    */
+    if triggers.is_empty() {
+        return Ok(());
+    }
+
+    let available = get_available_triggers(api_key)?;
+    let mut unknown: Vec<&str> = triggers
+        .iter()
+        .filter(|trigger| !available.contains(trigger))
+        .map(AlertTrigger::as_str)
+        .collect();
+    unknown.sort_unstable();
+    unknown.dedup();
+
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(ShodanError::Invalid(format!("unknown alert trigger(s): {}", unknown.join(", "))))
+    }
+}
+
+pub fn get_whitelist(api_key: &ApiKey, alert_id: &str) -> Result<HashMap<AlertTrigger, Vec<String>>, ShodanError> {
+    /*
+        Description:
+            Lists the ignored services of every enabled trigger of an alert, decoded from (GET /shodan/alert/{id}/info).
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            alert_id (&str): The unique ID that was returned by /shodan/alert.
+        Returns:
+            Result<HashMap<AlertTrigger, Vec<String>>, ShodanError>:
+                Ok(HashMap<AlertTrigger, Vec<String>>): Returns the ignored "ip:port" services for each enabled trigger.
+                Err(ShodanError): Returns an error if the request fails or returns an unexpected body.
+    */
+    let alert = shodan::get_alert_info_id(alert_id, api_key)?;
+    Ok(alert
+        .triggers
+        .into_iter()
+        .map(|(trigger, rule)| (trigger, rule.ignore))
+        .collect())
+}
+
+pub fn bulk_add_whitelist(api_key: &ApiKey, alert_id: &str, trigger: &AlertTrigger, services: &[(IpAddr, u16)], limiter: &mut RateLimiter) -> Vec<WhitelistResult> {
+    /*
+        Description:
+            Ignore a list of services for a trigger
+            Calls (PUT /shodan/alert/{id}/trigger/{trigger}/ignore/{service}) once per service, waiting on the rate limiter between calls.
+            A failing service does not stop the others, every outcome is reported.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            alert_id (&str): The unique ID that was returned by /shodan/alert.
+            trigger (&AlertTrigger): Trigger the services should be ignored for
+            services (&[(IpAddr, u16)]): The (ip, port) services to ignore, see read_whitelist_csv() to load them from a file.
+            Sent as "ip:port", IPv6 addresses being bracketed (ex: "[2001:db8::1]:443").
+            limiter (&mut RateLimiter): Rate limiter shared with the other calls of the program.
+        Returns:
+            Vec<WhitelistResult>: The outcome of every service, in the same order as services.
+    */
+    services
+        .iter()
+        .map(|(ip, port)| {
+            let service = SocketAddr::new(*ip, *port).to_string();
+            limiter.wait();
+            whitelist_result(service.clone(), shodan::add_whitelist(alert_id, trigger, &service, api_key))
+        })
+        .collect()
+}
+
+pub fn bulk_delete_whitelist(api_key: &ApiKey, alert_id: &str, trigger: &AlertTrigger, services: &[(IpAddr, u16)], limiter: &mut RateLimiter) -> Vec<WhitelistResult> {
+    /*
+        Description:
+            Stop ignoring a list of services for a trigger
+            Calls (DELETE /shodan/alert/{id}/trigger/{trigger}/ignore/{service}) once per service, waiting on the rate limiter between calls.
+            A failing service does not stop the others, every outcome is reported.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            alert_id (&str): The unique ID that was returned by /shodan/alert.
+            trigger (&AlertTrigger): Trigger the services should no longer be ignored for
+            services (&[(IpAddr, u16)]): The (ip, port) services to remove from the whitelist.
+            limiter (&mut RateLimiter): Rate limiter shared with the other calls of the program.
+        Returns:
+            Vec<WhitelistResult>: The outcome of every service, in the same order as services.
+    */
+    services
+        .iter()
+        .map(|(ip, port)| {
+            let service = SocketAddr::new(*ip, *port).to_string();
+            limiter.wait();
+            whitelist_result(service.clone(), shodan::delete_whitelist(alert_id, trigger, &service, api_key))
+        })
+        .collect()
+}
+
+pub fn read_whitelist_csv(path: &str) -> Result<Vec<(IpAddr, u16)>, ShodanError> {
+    /*
+        Description:
+            Reads (ip, port) services from a CSV file with one "ip,port" pair per line.
+            Empty lines, lines starting with "#" and an "ip,port" header line are skipped.
+        Parameters:
+            path (&str): The path of the CSV file.
+        Returns:
+            Result<Vec<(IpAddr, u16)>, ShodanError>:
+                Ok(Vec<(IpAddr, u16)>): Returns the services found in the file.
+                Err(ShodanError): Returns an error if the file can't be read or a line is not a valid "ip,port" pair.
+    */
+    let content = fs::read_to_string(path)?;
+    let mut services = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.eq_ignore_ascii_case("ip,port") {
+            continue;
+        }
+
+        let invalid = || ShodanError::Invalid(format!("{}:{}: expected \"ip,port\", got \"{}\"", path, index + 1, line));
+        let (ip, port) = line.split_once(',').ok_or_else(invalid)?;
+        let ip = ip.trim().parse::<IpAddr>().map_err(|_| invalid())?;
+        let port = port.trim().parse::<u16>().map_err(|_| invalid())?;
+        services.push((ip, port));
+    }
+
+    Ok(services)
+}
+
+pub fn get_alerts(api_key: &ApiKey) -> Result<Vec<Alert>, ShodanError> {
+    /*
+        Description:
+            Returns every network alert of the account.
+            Thin wrapper over shodan::get_alert_info() (GET /shodan/alert/info), kept for the callers of this module.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+        Returns:
+            Result<Vec<Alert>, ShodanError>:
+                Ok(Vec<Alert>): Returns the alerts of the account.
+                Err(ShodanError): Returns an error if the request fails or returns an unexpected body.
+    */
+    shodan::get_alert_info(api_key)
+}
+
+pub fn get_alerts_expiring_within(api_key: &ApiKey, window: Duration) -> Result<Vec<Alert>, ShodanError> {
+    /*
+        Description:
+            Returns the alerts that will expire within the given window from now, soonest first.
+            Alerts that never expire are left out.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            window (Duration): How far in the future to look (ex: Duration::from_secs(7 * 24 * 3600) for a week).
+        Returns:
+            Result<Vec<Alert>, ShodanError>:
+                Ok(Vec<Alert>): Returns the alerts expiring before now + window.
+                Err(ShodanError): Returns an error if the request fails or returns an unexpected body.
+    */
+    let deadline = helpers::unix_now().saturating_add(window.as_secs());
+    let mut expiring: Vec<Alert> = shodan::get_alert_info(api_key)?
+        .into_iter()
+        .filter(|alert| alert.expiration_timestamp().is_some_and(|expiration| expiration <= deadline))
+        .collect();
+    expiring.sort_by_key(Alert::expiration_timestamp);
+    Ok(expiring)
+}
+
+pub fn renew_alert(api_key: &ApiKey, alert_id: &str, expires: i32) -> Result<ImportedAlert, ShodanError> {
+    /*
+        Description:
+            Renew an alert
+            Shodan can't extend the lifetime of an existing alert, so the alert is recreated with the same name, IPs, triggers,
+            ignored services and notifiers and a new lifetime, then the old alert is deleted.
+            WARNING ! -> The old alert is only deleted once the new one is fully set up, if something fails both can exist.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            alert_id (&str): The unique ID of the alert to renew.
+            expires (i32): Number of seconds that the new alert should be active (0 = never expires).
+        Returns:
+            Result<ImportedAlert, ShodanError>:
+                Ok(ImportedAlert): Returns the ID of the new alert.
+                Err(ShodanError): Returns an error if one of the requests fails.
+    */
+    let alert = shodan::get_alert_info_id(alert_id, api_key)?;
+    let mut renewed = exported_alert_from(&alert);
+    renewed.expires = expires;
+
+    let created = recreate_alert(api_key, &renewed, |notifier| Some(notifier.id.clone()))?;
+    helpers::json_from_response(shodan::delete_alert(alert_id, api_key))?;
+    Ok(created)
+}
+
+fn whitelist_result(service: String, result: Result<Response, ReqwestError>) -> WhitelistResult {
+    match helpers::json_from_response(result) {
+        Ok(_) => WhitelistResult { service, success: true, error: None },
+        Err(err) => WhitelistResult { service, success: false, error: Some(err.to_string()) },
+    }
+}
+
+fn exported_alert_from(alert: &Alert) -> ExportedAlert {
+    let mut triggers: Vec<ExportedTrigger> = alert
+        .triggers
+        .iter()
+        .map(|(trigger, rule)| ExportedTrigger { name: trigger.clone(), ignore: rule.ignore.clone() })
+        .collect();
+    triggers.sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+
+    let notifiers = alert
+        .notifiers
+        .iter()
+        .map(|notifier| ExportedNotifier {
+            id: notifier.id.clone(),
+            provider: notifier.provider.clone(),
+            description: notifier.description.clone(),
+        })
+        .collect();
+
+    ExportedAlert {
+        name: alert.name.clone(),
+        ips: alert.filters.ip.clone(),
+        expires: i32::try_from(alert.expires).unwrap_or(0),
+        triggers,
+        notifiers,
+    }
+}
+
+fn recreate_alert<F>(api_key: &ApiKey, alert: &ExportedAlert, notifier_id_for: F) -> Result<ImportedAlert, ShodanError>
+where
+    F: Fn(&ExportedNotifier) -> Option<String>,
+{
+    let ips: Vec<&str> = alert.ips.iter().map(String::as_str).collect();
+    let alert_id = shodan::create_alert(api_key, &alert.name, ips, alert.expires)?.id;
+
+    if !alert.triggers.is_empty() {
+        let triggers: Vec<AlertTrigger> = alert.triggers.iter().map(|trigger| trigger.name.clone()).collect();
+        helpers::json_from_response(shodan::add_trigger(&alert_id, &triggers, api_key))?;
+    }
+
+    for trigger in &alert.triggers {
+        for service in &trigger.ignore {
+            helpers::json_from_response(shodan::add_whitelist(&alert_id, &trigger.name, service, api_key))?;
+        }
+    }
+
+    let mut skipped_notifiers = Vec::new();
+    for notifier in &alert.notifiers {
+        match notifier_id_for(notifier) {
+            Some(notifier_id) => {
+                helpers::json_from_response(shodan::add_notifier_alert(&alert_id, &notifier_id, api_key))?;
+            }
+            None => {
+                skipped_notifiers.push(notifier.description.clone().unwrap_or_else(|| notifier.id.clone()));
+            }
+        }
+    }
+
+    Ok(ImportedAlert { name: alert.name.clone(), id: alert_id, skipped_notifiers })
+}
+
+fn notifier_ids_by_description(api_key: &ApiKey) -> Result<HashMap<String, String>, ShodanError> {
+    // Descriptions shared by several notifiers are left out, there is no way to tell which one the export meant
+    let mut ids: HashMap<String, Vec<String>> = HashMap::new();
+    for notifier in shodan::get_notifier(api_key)? {
+        if let Some(description) = notifier.description {
+            ids.entry(description).or_default().push(notifier.id);
+        }
+    }
+    Ok(ids
+        .into_iter()
+        .filter(|(_, ids)| ids.len() == 1)
+        .map(|(description, mut ids)| (description, ids.remove(0)))
+        .collect())
+}
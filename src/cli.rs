@@ -0,0 +1,384 @@
+use std::net::{IpAddr, SocketAddr};
+use clap::{Parser, Subcommand};
+use sh3nr0n_rust_membership::models::{AlertTrigger, DnsRecordType, Order, SortBy};
+use sh3nr0n_rust_membership::keypool::Rotation;
+use sh3nr0n_rust_membership::output::OutputFormat;
+
+/*
+    Description:
+        Command line of the binary, one subcommand per group of the Shodan API.
+        Run `sh3nr0n-rust-membership help <subcommand>` for the arguments of each one.
+*/
+#[derive(Parser)]
+#[command(version, about = "Command-line client for the Shodan REST and Streaming APIs")]
+pub struct Cli {
+    #[arg(
+        long,
+        global = true,
+        help = "Shodan API key, otherwise taken from $SHODAN_API_KEY, the config file ($XDG_CONFIG_HOME/shodan/api_key) or --key-file"
+    )]
+    pub key: Option<String>,
+
+    #[arg(long, global = true, value_name = "PATH", help = "File holding your Shodan API key, used when no other key is found")]
+    pub key_file: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        conflicts_with = "key",
+        help = "File holding several Shodan API keys, one per line: reads go to the next key, skipping the rate limited and out of credits ones, other commands use the first key"
+    )]
+    pub key_pool: Option<String>,
+
+    #[arg(long, global = true, default_value = "round-robin", value_parser = parse_rotation, help = "How --key-pool picks the key of a request: round-robin or least-used")]
+    pub rotation: Rotation,
+
+    #[arg(long, global = true, default_value = "json", value_parser = parse_format, help = "Output format: table, json, jsonl or csv")]
+    pub format: OutputFormat,
+
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        help = "Comma-separated columns to keep, nested fields with dotted names (ex: ip_str,port,org,location.country_code)"
+    )]
+    pub fields: Vec<String>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/*
+    Description:
+        Subcommands of the binary: a stream printed as it comes, host lookups spread across the key pool, or one of the
+        RequestCommand printed once it is done.
+*/
+#[derive(Subcommand)]
+pub enum Command {
+    #[command(flatten)]
+    Request(RequestCommand),
+    #[command(about = "Every service found on several hosts, spread across the keys of --key-pool (GET /shodan/host/{ip}), unknown hosts being left out")]
+    Hosts {
+        #[arg(required = true, help = "Host IP addresses")]
+        ips: Vec<IpAddr>,
+    },
+    #[command(about = "Banners of the monitored networks, as they are found (stream.shodan.io/shodan/alert)")]
+    Stream {
+        #[arg(help = "Only stream the banners of this network alert")]
+        alert_id: Option<String>,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+pub enum RequestCommand {
+    #[command(about = "Every service found on a host (GET /shodan/host/{ip})")]
+    Host {
+        #[arg(help = "Host IP address")]
+        ip: IpAddr,
+    },
+    #[command(about = "Number of results of a search, with facets, without using query credits (GET /shodan/host/count)")]
+    Count {
+        #[arg(help = "Shodan search query (ex: \"apache country:DE\")")]
+        query: String,
+        #[arg(long, default_value = "", help = "Comma-separated list of facets (ex: \"country:10,org\")")]
+        facets: String,
+    },
+    #[command(about = "Search Shodan (GET /shodan/host/search)")]
+    Search {
+        #[arg(help = "Shodan search query (ex: \"apache country:DE\")")]
+        query: String,
+        #[arg(long, default_value = "", help = "Comma-separated list of facets (ex: \"country:10,org\")")]
+        facets: String,
+        #[arg(long, default_value_t = 1, help = "Page of 100 results, starting at 1 (pages after the first cost 1 query credit each)")]
+        page: u32,
+    },
+    #[command(about = "Break a search query into its filters and search terms (GET /shodan/host/search/tokens)")]
+    Tokens {
+        #[arg(help = "Shodan search query (ex: \"apache country:DE\")")]
+        query: String,
+    },
+    #[command(about = "On-demand scanning", subcommand)]
+    Scan(ScanCommand),
+    #[command(about = "Network alerts", subcommand)]
+    Alert(AlertCommand),
+    #[command(about = "Notification services of the network alerts", subcommand)]
+    Notifier(NotifierCommand),
+    #[command(about = "Directory of saved search queries", subcommand)]
+    Directory(DirectoryCommand),
+    #[command(about = "DNS lookups", subcommand)]
+    Dns(DnsCommand),
+    #[command(about = "Shodan account linked to the API key (GET /account/profile)")]
+    Account,
+    #[command(about = "API plan, credits left and usage limits of the API key (GET /api-info)")]
+    Info,
+    #[command(about = "Your IP address as seen from the Internet (GET /tools/myip)")]
+    Myip,
+}
+
+#[derive(Clone, Subcommand)]
+pub enum ScanCommand {
+    #[command(about = "Ports Shodan is crawling (GET /shodan/ports)")]
+    Ports,
+    #[command(about = "Protocols usable with on-demand scans (GET /shodan/protocols)")]
+    Protocols,
+    #[command(about = "Request a scan (POST /shodan/scan)")]
+    Create {
+        #[arg(help = "Comma-separated IPs/ netblocks, or a JSON object of {ip: [[port, protocol], ...]}")]
+        targets: String,
+    },
+    #[command(about = "Scans launched by the account (GET /shodan/scans)")]
+    List,
+    #[command(about = "Progress of a scan (GET /shodan/scan/{id})")]
+    Status {
+        #[arg(help = "Scan ID returned by scan create")]
+        id: String,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+pub enum AlertCommand {
+    #[command(about = "Create a network alert (POST /shodan/alert)")]
+    Create {
+        #[arg(help = "Name of the network alert")]
+        name: String,
+        #[arg(long = "ip", required = true, help = "IP or netblock to monitor, repeat for several")]
+        ips: Vec<String>,
+        #[arg(long, default_value_t = 0, help = "Lifetime of the alert in seconds, 0 for no expiration")]
+        expires: i32,
+    },
+    #[command(about = "Every network alert of the account (GET /shodan/alert/info)")]
+    List,
+    #[command(about = "Details of a network alert (GET /shodan/alert/{id}/info)")]
+    Info {
+        #[arg(help = "Network alert ID")]
+        id: String,
+    },
+    #[command(about = "Delete a network alert (DELETE /shodan/alert/{id})")]
+    Delete {
+        #[arg(help = "Network alert ID")]
+        id: String,
+    },
+    #[command(about = "Replace the monitored IPs of a network alert (POST /shodan/alert/{id})")]
+    Edit {
+        #[arg(help = "Network alert ID")]
+        id: String,
+        #[arg(long = "ip", required = true, help = "IP or netblock to monitor, repeat for several")]
+        ips: Vec<String>,
+    },
+    #[command(about = "Network alerts expiring soon")]
+    Expiring {
+        #[arg(long, default_value_t = 7, help = "Expiring in the next days")]
+        days: u64,
+    },
+    #[command(about = "Recreate an alert with a new expiration, keeping its triggers, notifiers and whitelist")]
+    Renew {
+        #[arg(help = "Network alert ID")]
+        id: String,
+        #[arg(long, help = "Lifetime of the renewed alert in seconds, 0 for no expiration")]
+        expires: i32,
+    },
+    #[command(about = "Triggers available for the network alerts (GET /shodan/alert/triggers)")]
+    Triggers,
+    #[command(about = "Enable triggers on a network alert (PUT /shodan/alert/{id}/trigger/{trigger})")]
+    AddTrigger {
+        #[arg(help = "Network alert ID")]
+        id: String,
+        #[arg(required = true, value_parser = parse_trigger, help = "Trigger names (ex: new_service vulnerable)")]
+        triggers: Vec<AlertTrigger>,
+    },
+    #[command(about = "Disable triggers of a network alert (DELETE /shodan/alert/{id}/trigger/{trigger})")]
+    RemoveTrigger {
+        #[arg(help = "Network alert ID")]
+        id: String,
+        #[arg(required = true, value_parser = parse_trigger, help = "Trigger names (ex: new_service vulnerable)")]
+        triggers: Vec<AlertTrigger>,
+    },
+    #[command(about = "Services ignored by the triggers of a network alert")]
+    Whitelist {
+        #[arg(help = "Network alert ID")]
+        id: String,
+    },
+    #[command(about = "Ignore services for a trigger (PUT /shodan/alert/{id}/trigger/{trigger}/ignore/{service})")]
+    Ignore {
+        #[arg(help = "Network alert ID")]
+        id: String,
+        #[arg(value_parser = parse_trigger, help = "Trigger name (ex: new_service)")]
+        trigger: AlertTrigger,
+        #[arg(value_parser = parse_service, help = "Services as ip:port")]
+        services: Vec<(IpAddr, u16)>,
+        #[arg(long, help = "CSV file with one \"ip,port\" pair per line")]
+        csv: Option<String>,
+    },
+    #[command(about = "Stop ignoring services for a trigger (DELETE /shodan/alert/{id}/trigger/{trigger}/ignore/{service})")]
+    Unignore {
+        #[arg(help = "Network alert ID")]
+        id: String,
+        #[arg(value_parser = parse_trigger, help = "Trigger name (ex: new_service)")]
+        trigger: AlertTrigger,
+        #[arg(value_parser = parse_service, help = "Services as ip:port")]
+        services: Vec<(IpAddr, u16)>,
+        #[arg(long, help = "CSV file with one \"ip,port\" pair per line")]
+        csv: Option<String>,
+    },
+    #[command(about = "Notify a network alert through a notifier (PUT /shodan/alert/{id}/notifier/{notifier_id})")]
+    AddNotifier {
+        #[arg(help = "Network alert ID")]
+        id: String,
+        #[arg(help = "Notifier ID, \"default\" for the account e-mail")]
+        notifier_id: String,
+    },
+    #[command(about = "Stop notifying a network alert through a notifier (DELETE /shodan/alert/{id}/notifier/{notifier_id})")]
+    RemoveNotifier {
+        #[arg(help = "Network alert ID")]
+        id: String,
+        #[arg(help = "Notifier ID, \"default\" for the account e-mail")]
+        notifier_id: String,
+    },
+    #[command(about = "Write every network alert of the account to a JSON file")]
+    Export {
+        #[arg(help = "Path of the JSON file")]
+        path: String,
+    },
+    #[command(about = "Create the network alerts of a file written by export")]
+    Import {
+        #[arg(help = "Path of the JSON file")]
+        path: String,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+pub enum NotifierCommand {
+    #[command(about = "Notifiers of the account (GET /notifier)")]
+    List,
+    #[command(about = "Notification providers and their arguments (GET /notifier/provider)")]
+    Providers,
+    #[command(about = "Create a notifier (POST /notifier)")]
+    Create {
+        #[arg(help = "Provider name (ex: email, slack, webhook)")]
+        provider: String,
+        #[arg(help = "Description of the notifier")]
+        description: String,
+        #[arg(long = "arg", value_parser = parse_key_value, help = "Provider argument as name=value (ex: to=me@example.com), repeat for several")]
+        args: Vec<(String, String)>,
+    },
+    #[command(about = "Details of a notifier (GET /notifier/{id})")]
+    Info {
+        #[arg(help = "Notifier ID")]
+        id: String,
+    },
+    #[command(about = "Delete a notifier (DELETE /notifier/{id})")]
+    Delete {
+        #[arg(help = "Notifier ID")]
+        id: String,
+    },
+    #[command(about = "Replace the arguments of a notifier (PUT /notifier/{id})")]
+    Edit {
+        #[arg(help = "Notifier ID")]
+        id: String,
+        #[arg(long = "arg", required = true, value_parser = parse_key_value, help = "Provider argument as name=value, repeat for several")]
+        args: Vec<(String, String)>,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+pub enum DirectoryCommand {
+    #[command(about = "Saved search queries (GET /shodan/query)")]
+    List {
+        #[arg(long, default_value_t = 1, help = "Page of 10 queries, starting at 1")]
+        page: u32,
+        #[arg(long, value_parser = parse_sort, help = "votes or timestamp")]
+        sort: Option<SortBy>,
+        #[arg(long, value_parser = parse_order, help = "asc or desc")]
+        order: Option<Order>,
+        #[arg(long, conflicts_with = "page", help = "Every page of the directory")]
+        all: bool,
+    },
+    #[command(about = "Search the saved search queries (GET /shodan/query/search)")]
+    Search {
+        #[arg(help = "What to search for in the saved queries")]
+        query: String,
+        #[arg(long, default_value_t = 1, help = "Page of 10 queries, starting at 1")]
+        page: u32,
+        #[arg(long, conflicts_with = "page", help = "Every page of the results")]
+        all: bool,
+    },
+    #[command(about = "Most popular tags of the saved search queries (GET /shodan/query/tags)")]
+    Tags {
+        #[arg(long, default_value_t = 10, help = "Number of tags")]
+        size: u32,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+pub enum DnsCommand {
+    #[command(about = "Subdomains and DNS records of a domain, 1 query credit per page (GET /dns/domain/{domain})")]
+    Domain {
+        #[arg(help = "Domain name (ex: cnn.com)")]
+        domain: String,
+        #[arg(long, help = "Include historical DNS data")]
+        history: bool,
+        #[arg(long = "type", value_parser = parse_record_type, help = "Only this record type (ex: A, AAAA, CNAME, MX)")]
+        record_type: Option<DnsRecordType>,
+        #[arg(long, default_value_t = 1, help = "Page of 100 records, starting at 1")]
+        page: u32,
+        #[arg(long, conflicts_with = "page", help = "Every record, paging until the end")]
+        all: bool,
+    },
+    #[command(about = "IP of hostnames (GET /dns/resolve)")]
+    Resolve {
+        #[arg(required = true, help = "Hostnames to resolve")]
+        hostnames: Vec<String>,
+    },
+    #[command(about = "Hostnames of IPs (GET /dns/reverse)")]
+    Reverse {
+        #[arg(required = true, help = "IPs to look up")]
+        ips: Vec<IpAddr>,
+    },
+}
+
+fn parse_rotation(value: &str) -> Result<Rotation, String> {
+    match value {
+        "round-robin" => Ok(Rotation::RoundRobin),
+        "least-used" => Ok(Rotation::LeastUsed),
+        _ => Err(format!("expected round-robin or least-used, got \"{}\"", value)),
+    }
+}
+
+fn parse_format(value: &str) -> Result<OutputFormat, String> {
+    value.parse().map_err(|err: sh3nr0n_rust_membership::error::ShodanError| err.to_string())
+}
+
+fn parse_trigger(value: &str) -> Result<AlertTrigger, String> {
+    Ok(AlertTrigger::from(value))
+}
+
+fn parse_record_type(value: &str) -> Result<DnsRecordType, String> {
+    Ok(DnsRecordType::from(value))
+}
+
+fn parse_service(value: &str) -> Result<(IpAddr, u16), String> {
+    let service: SocketAddr = value.parse().map_err(|_| format!("expected ip:port, got \"{}\"", value))?;
+    Ok((service.ip(), service.port()))
+}
+
+fn parse_key_value(value: &str) -> Result<(String, String), String> {
+    let (name, value) = value.split_once('=').ok_or_else(|| format!("expected name=value, got \"{}\"", value))?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+fn parse_sort(value: &str) -> Result<SortBy, String> {
+    match value {
+        "votes" => Ok(SortBy::Votes),
+        "timestamp" => Ok(SortBy::Timestamp),
+        _ => Err(format!("expected votes or timestamp, got \"{}\"", value)),
+    }
+}
+
+fn parse_order(value: &str) -> Result<Order, String> {
+    match value {
+        "asc" => Ok(Order::Asc),
+        "desc" => Ok(Order::Desc),
+        _ => Err(format!("expected asc or desc, got \"{}\"", value)),
+    }
+}
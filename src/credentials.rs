@@ -0,0 +1,390 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use reqwest::{Error as ReqwestError, Url};
+use crate::error::ShodanError;
+use crate::helpers;
+
+// Environment variable holding the API key
+pub const API_KEY_ENV: &str = "SHODAN_API_KEY";
+
+// Length of a Shodan API key (letters and digits only)
+const API_KEY_LENGTH: usize = 32;
+
+// Service and user of the API key entry in the system keyring (with the "keyring" feature)
+pub const KEYRING_SERVICE: &str = "shodan";
+pub const KEYRING_USER: &str = "api_key";
+
+// Written in place of the key query parameter of URLs that are displayed
+const REDACTED: &str = "REDACTED";
+
+/*
+    Description:
+        Where resolve_api_key() found the API key.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySource {
+    Argument,
+    Environment,
+    Keyring,
+    ConfigFile(PathBuf),
+    LegacyFile(PathBuf),
+}
+
+impl fmt::Display for KeySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeySource::Argument => write!(f, "the command line"),
+            KeySource::Environment => write!(f, "${}", API_KEY_ENV),
+            KeySource::Keyring => write!(f, "the system keyring"),
+            KeySource::ConfigFile(path) => write!(f, "config file {}", path.display()),
+            KeySource::LegacyFile(path) => write!(f, "key file {}", path.display()),
+        }
+    }
+}
+
+/*
+    Description:
+        API key wrapper, so the key can't end up in a log by accident: Debug and Display only show it redacted
+        (see redact_api_key()). Use expose() to send it to Shodan.
+*/
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    pub fn new(key: impl Into<String>) -> Self {
+        ApiKey(key.into())
+    }
+
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ApiKey").field(&redact_api_key(&self.0)).finish()
+    }
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", redact_api_key(&self.0))
+    }
+}
+
+/*
+    Description:
+        API key returned by resolve_api_key(), trimmed and validated, and where it was found.
+*/
+#[derive(Debug, Clone)]
+pub struct ResolvedKey {
+    pub key: ApiKey,
+    pub source: KeySource,
+}
+
+impl fmt::Display for ResolvedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (from {})", self.key, self.source)
+    }
+}
+
+pub fn resolve_api_key(explicit: Option<&str>, legacy_file: Option<&str>) -> Result<ResolvedKey, ShodanError> {
+    /*
+        Description:
+            API key resolution
+            Looks for the API key in this order and returns the first one found:
+                1. explicit, the key given on the command line.
+                2. The SHODAN_API_KEY environment variable (ignored when empty).
+                3. With the "keyring" feature only, the system keyring entry of service KEYRING_SERVICE and user KEYRING_USER
+                   (ignored when missing or when no keyring is available, ex: on a headless server).
+                4. The config file, see config_key_path() (ignored when missing).
+                5. legacy_file, the key file given before this resolution existed (ignored when missing).
+            The key found is trimmed and validated, a key that is found but invalid is an error rather than skipped.
+        Parameters:
+            explicit (Option<&str>): API key given explicitly, if any.
+            legacy_file (Option<&str>): Path of a file holding the API key, if any.
+        Returns:
+            Result<ResolvedKey, ShodanError>:
+                Ok(ResolvedKey): Returns the key and where it was found.
+                Err(ShodanError): Returns an error if no key was found, a key file can't be read or the key is not valid.
+    */
+    let (key, source) = find_api_key(explicit, legacy_file)?.ok_or_else(|| {
+        let config = config_key_path().map(|path| path.display().to_string()).unwrap_or_else(|| "the config file".to_string());
+        ShodanError::Invalid(format!("no API key found: pass it with --key, set ${} or write it to {}", API_KEY_ENV, config))
+    })?;
+
+    if let Some(problem) = key_format_problem(&key) {
+        return Err(ShodanError::Invalid(format!("API key from {} is not valid: {}", source, problem)));
+    }
+    Ok(ResolvedKey { key: ApiKey::new(key), source })
+}
+
+pub fn read_api_keys(path: &str) -> Result<Vec<ApiKey>, ShodanError> {
+    /*
+        Description:
+            Reads a pool of API keys (see keypool.rs) from a file holding one key per line, empty lines and lines starting
+            with "#" being ignored.
+        Parameters:
+            path (&str): Path of the file.
+        Returns:
+            Result<Vec<ApiKey>, ShodanError>:
+                Ok(Vec<ApiKey>): Returns the keys, in the order of the file.
+                Err(ShodanError): Returns an error if the file can't be read or a key is not valid (the error gives its line, not the key).
+    */
+    let content = fs::read_to_string(path)?;
+    let mut keys = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let key = line.trim();
+        if key.is_empty() || key.starts_with('#') {
+            continue;
+        }
+        if let Some(problem) = key_format_problem(key) {
+            return Err(ShodanError::Invalid(format!("API key on line {} of {} is not valid: {}", number + 1, path, problem)));
+        }
+        keys.push(ApiKey::new(key));
+    }
+    Ok(keys)
+}
+
+pub fn config_key_path() -> Option<PathBuf> {
+    /*
+        Description:
+            Path of the API key in the XDG config directory: $XDG_CONFIG_HOME/shodan/api_key, or ~/.config/shodan/api_key
+            when XDG_CONFIG_HOME is not set. None when neither XDG_CONFIG_HOME nor HOME is set.
+    */
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").filter(|path| !path.is_empty()).map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("shodan").join("api_key"))
+}
+
+pub fn validate_api_key(key: &str) -> Result<(), ShodanError> {
+    /*
+        Description:
+            Checks the format of an API key (32 letters and digits) without sending it anywhere.
+            The error never contains the key itself.
+    */
+    match key_format_problem(key) {
+        Some(problem) => Err(ShodanError::Invalid(format!("API key is not valid: {}", problem))),
+        None => Ok(()),
+    }
+}
+
+pub fn redact_api_key(key: &str) -> String {
+    /*
+        Description:
+            Returns the API key in a form safe to display or log: its first and last 4 characters only (ex: "AbCd...wXyZ"),
+            or only "..." for keys too short to show anything.
+    */
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() < 16 {
+        return "...".to_string();
+    }
+    let start: String = chars[..4].iter().collect();
+    let end: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}...{}", start, end)
+}
+
+pub fn redact_url(url: &mut Url) {
+    /*
+        Description:
+            Replaces the value of the key query parameter of url (ex: "?key=AbCd...&query=x" -> "?key=REDACTED&query=x"),
+            every other parameter being kept as it is.
+    */
+    if !url.query_pairs().any(|(name, _)| name == "key") {
+        return;
+    }
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| {
+            let value = if name == "key" { REDACTED.to_string() } else { value.into_owned() };
+            (name.into_owned(), value)
+        })
+        .collect();
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+}
+
+pub fn redact_error(mut err: ReqwestError) -> ReqwestError {
+    /*
+        Description:
+            Returns err with the API key removed from its URL (see redact_url()), reqwest printing the full URL
+            with both Display and Debug. Every ReqwestError turned into a ShodanError goes through here.
+    */
+    if let Some(url) = err.url_mut() {
+        redact_url(url);
+    }
+    err
+}
+
+pub fn redact_io_error(err: io::Error) -> io::Error {
+    /*
+        Description:
+            Same as redact_error(), for the io::Error wrapping a ReqwestError returned while reading a body
+            (ex: a stream read line by line). Other errors are returned as they are.
+    */
+    if !err.get_ref().is_some_and(|inner| inner.is::<ReqwestError>()) {
+        return err;
+    }
+    let kind = err.kind();
+    match err.into_inner().map(|inner| inner.downcast::<ReqwestError>()) {
+        Some(Ok(inner)) => io::Error::new(kind, redact_error(*inner)),
+        Some(Err(inner)) => io::Error::new(kind, inner),
+        None => io::Error::from(kind),
+    }
+}
+
+pub fn redact_key_params(text: &str) -> String {
+    /*
+        Description:
+            Replaces the value of every key query parameter found in text (ex: a URL, a query string or an error formatted with {:?})
+            with "REDACTED". For text that can't be redacted with redact_url() or redact_error().
+    */
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find("key=") {
+        let (before, after) = rest.split_at(index + "key=".len());
+        redacted.push_str(before);
+        let is_parameter = index == 0 || before[..index].ends_with(['?', '&', '"']);
+        let end = after
+            .find(|c: char| c == '&' || c == '#' || c == '"' || c == '\'' || c == ')' || c.is_whitespace())
+            .unwrap_or(after.len());
+        if is_parameter && end > 0 {
+            redacted.push_str(REDACTED);
+            rest = &after[end..];
+        } else {
+            rest = after;
+        }
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+fn find_api_key(explicit: Option<&str>, legacy_file: Option<&str>) -> Result<Option<(String, KeySource)>, ShodanError> {
+    // First non empty key, in the order of resolve_api_key(), files being read only when the previous places had nothing
+    if let Some(key) = explicit.map(str::trim).filter(|key| !key.is_empty()) {
+        return Ok(Some((key.to_string(), KeySource::Argument)));
+    }
+    if let Some(key) = env::var(API_KEY_ENV).ok().map(|key| key.trim().to_string()).filter(|key| !key.is_empty()) {
+        return Ok(Some((key, KeySource::Environment)));
+    }
+    #[cfg(feature = "keyring")]
+    if let Some(key) = keyring_api_key() {
+        return Ok(Some((key, KeySource::Keyring)));
+    }
+    let files = [
+        config_key_path().map(|path| (path.clone(), KeySource::ConfigFile(path))),
+        legacy_file.map(|path| (PathBuf::from(path), KeySource::LegacyFile(PathBuf::from(path)))),
+    ];
+    for (path, source) in files.into_iter().flatten() {
+        if !path.is_file() {
+            continue;
+        }
+        let key = helpers::read_api_key(&path.to_string_lossy())?;
+        if !key.is_empty() {
+            return Ok(Some((key, source)));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_api_key() -> Option<String> {
+    // Any keyring error (no entry, no keyring service running, access denied) means there is no key there
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).ok()?;
+    let key = entry.get_password().ok()?;
+    Some(key.trim().to_string()).filter(|key| !key.is_empty())
+}
+
+fn key_format_problem(key: &str) -> Option<String> {
+    let length = key.chars().count();
+    if length != API_KEY_LENGTH {
+        return Some(format!("expected {} characters, got {}", API_KEY_LENGTH, length));
+    }
+    if !key.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Some("expected only letters and digits".to_string());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::net::TcpListener;
+    use reqwest::{Error as ReqwestError, Url};
+    use crate::error::ShodanError;
+    use super::*;
+
+    const KEY: &str = "AbCdEfGh0123456789IjKlMnOpQrStUv";
+
+    fn assert_redacted(text: &str) {
+        assert!(!text.contains(KEY), "API key found in \"{}\"", text);
+    }
+
+    fn failed_request() -> ReqwestError {
+        // Request to a port nothing listens on, so it fails with the key in its URL
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        reqwest::blocking::get(format!("http://127.0.0.1:{}/shodan/host/8.8.8.8?key={}", port, KEY)).unwrap_err()
+    }
+
+    #[test]
+    fn api_key_is_redacted() {
+        let key = ApiKey::new(KEY);
+        assert_redacted(&format!("{}", key));
+        assert_redacted(&format!("{:?}", key));
+        assert_eq!(key.expose(), KEY);
+    }
+
+    #[test]
+    fn resolved_key_is_redacted() {
+        let resolved = ResolvedKey { key: ApiKey::new(KEY), source: KeySource::Argument };
+        assert_redacted(&format!("{}", resolved));
+        assert_redacted(&format!("{:?}", resolved));
+    }
+
+    #[test]
+    fn http_error_is_redacted() {
+        let raw = failed_request();
+        assert!(format!("{:?}", raw).contains(KEY), "the unredacted error should hold the key");
+
+        let err = ShodanError::from(raw);
+        assert_redacted(&format!("{}", err));
+        assert_redacted(&format!("{:?}", err));
+        assert!(format!("{}", err).contains("key=REDACTED"));
+    }
+
+    #[test]
+    fn io_error_is_redacted() {
+        let err = redact_io_error(io::Error::other(failed_request()));
+        assert_redacted(&format!("{}", err));
+        assert_redacted(&format!("{:?}", err));
+        assert_redacted(&format!("{}", ShodanError::from(io::Error::other(failed_request()))));
+    }
+
+    #[test]
+    fn url_is_redacted() {
+        let mut url = Url::parse(&format!("https://api.shodan.io/shodan/host/search?key={}&query=apache", KEY)).unwrap();
+        redact_url(&mut url);
+        assert_redacted(url.as_str());
+        assert_eq!(url.as_str(), "https://api.shodan.io/shodan/host/search?key=REDACTED&query=apache");
+    }
+
+    #[test]
+    fn key_params_are_redacted() {
+        let url = format!("https://api.shodan.io/shodan/host/search?key={}&query=apache", KEY);
+        assert_eq!(redact_key_params(&url), "https://api.shodan.io/shodan/host/search?key=REDACTED&query=apache");
+
+        let at_end = format!("https://api.shodan.io/tools/myip?page=2&key={}", KEY);
+        assert_eq!(redact_key_params(&at_end), "https://api.shodan.io/tools/myip?page=2&key=REDACTED");
+
+        let debug = format!("Url {{ query: Some(\"key={}\") }}", KEY);
+        assert_eq!(redact_key_params(&debug), "Url { query: Some(\"key=REDACTED\") }");
+
+        assert_eq!(redact_key_params("monkey=banana&key=secret"), "monkey=banana&key=REDACTED");
+        assert_eq!(redact_key_params("?monkey=banana"), "?monkey=banana");
+        assert_eq!(redact_key_params("?key="), "?key=");
+    }
+}
@@ -0,0 +1,177 @@
+use std::cmp::Ordering;
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use crate::error::ShodanError;
+use crate::helpers::{self, RateLimiter};
+use crate::models::HostInfo;
+
+/*
+    Description:
+        Vulnerability as returned by CVEDB (https://cvedb.shodan.io). cvss is the highest available CVSS score,
+        epss the probability of exploitation in the next 30 days (0 to 1) and kev whether CISA lists it as Known Exploited.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cve {
+    pub cve_id: String,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub cvss: Option<f64>,
+    #[serde(default)]
+    pub cvss_version: Option<f64>,
+    #[serde(default)]
+    pub epss: Option<f64>,
+    #[serde(default)]
+    pub ranking_epss: Option<f64>,
+    #[serde(default)]
+    pub kev: bool,
+    #[serde(default)]
+    pub propose_action: Option<String>,
+    #[serde(default)]
+    pub published_time: Option<String>,
+    #[serde(default)]
+    pub references: Vec<String>,
+}
+
+/*
+    Description:
+        What to search CVEDB for with search_cves(): a product name (ex: "nginx") or a CPE 2.3 string
+        (ex: "cpe:2.3:a:nginx:nginx:1.18.0").
+*/
+#[derive(Debug, Clone, Copy)]
+pub enum CveQuery<'a> {
+    Product(&'a str),
+    Cpe(&'a str),
+}
+
+/*
+    Description:
+        One CVE found on a host by enrich_host(), with the ports it was reported on and its CVEDB details
+        (None when CVEDB does not know the CVE).
+*/
+#[derive(Debug, Clone, Serialize)]
+pub struct HostVuln {
+    pub cve_id: String,
+    pub ports: Vec<u16>,
+    pub details: Option<Cve>,
+}
+
+#[derive(Deserialize)]
+struct CveListing {
+    cves: Vec<Cve>,
+}
+
+pub fn get_cve(cve_id: &str) -> Result<Cve, ShodanError> {
+    /*
+        Description:
+            CVE Lookup
+            Returns the details of a CVE: summary, CVSS, EPSS, KEV status, references... No API key needed.
+            (GET https://cvedb.shodan.io/cve/{cve_id})
+        Parameters:
+            cve_id (&str): The CVE ID; example "CVE-2021-44228"
+        Returns:
+            Result<Cve, ShodanError>:
+                Ok(Cve): Returns the CVE, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or CVEDB does not know the CVE (404).
+    */
+    let client = Client::new();
+    let url = format!("https://cvedb.shodan.io/cve/{}", cve_id);
+    let response = helpers::send("get_cve", client.get(&url));
+    helpers::decode_response(response)
+}
+
+pub fn search_cves(query: CveQuery, skip: u32, limit: u32) -> Result<Vec<Cve>, ShodanError> {
+    /*
+        Description:
+            Search CVEs
+            Returns the CVEs affecting a product or a CPE. No API key needed.
+            (GET https://cvedb.shodan.io/cves)
+        Parameters:
+            query (CveQuery): The product name or CPE 2.3 string to search for.
+            skip (u32): Number of CVEs to skip, to page through the results.
+            limit (u32): Maximum number of CVEs to return.
+        Returns:
+            Result<Vec<Cve>, ShodanError>:
+                Ok(Vec<Cve>): Returns the matching CVEs, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
+    */
+    let (name, value) = match query {
+        CveQuery::Product(product) => ("product", product),
+        CveQuery::Cpe(cpe) => ("cpe23", cpe),
+    };
+    let client = Client::new();
+    let request = client
+        .get("https://cvedb.shodan.io/cves")
+        .query(&[(name, value.to_string()), ("skip", skip.to_string()), ("limit", limit.to_string())]);
+    let response = helpers::send("search_cves", request);
+    let listing: CveListing = helpers::decode_response(response)?;
+    Ok(listing.cves)
+}
+
+pub fn get_kev_cves(skip: u32, limit: u32) -> Result<Vec<Cve>, ShodanError> {
+    /*
+        Description:
+            Known Exploited Vulnerabilities
+            Returns the CVEs listed in the CISA KEV catalog. No API key needed.
+            (GET https://cvedb.shodan.io/cves?is_kev=true)
+        Parameters:
+            skip (u32): Number of CVEs to skip, to page through the results.
+            limit (u32): Maximum number of CVEs to return.
+        Returns:
+            Result<Vec<Cve>, ShodanError>:
+                Ok(Vec<Cve>): Returns the KEV entries, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
+    */
+    let client = Client::new();
+    let request = client
+        .get("https://cvedb.shodan.io/cves")
+        .query(&[("is_kev", "true".to_string()), ("skip", skip.to_string()), ("limit", limit.to_string())]);
+    let response = helpers::send("get_kev_cves", request);
+    let listing: CveListing = helpers::decode_response(response)?;
+    Ok(listing.cves)
+}
+
+pub fn enrich_host(host: &HostInfo, limiter: &mut RateLimiter) -> Result<Vec<HostVuln>, ShodanError> {
+    /*
+        Description:
+            Vulnerability enrichment of a host
+            Collects the CVE IDs of a host (host level "vulns" and the "vulns" of every banner), looks each one up in CVEDB
+            and sorts them by risk: KEV first, then highest EPSS, then highest CVSS. CVEs unknown to CVEDB come last.
+        Parameters:
+            host (&HostInfo): Host decoded from (GET /shodan/host/{ip}).
+            limiter (&mut RateLimiter): Rate limiter shared with the other calls of the program.
+        Returns:
+            Result<Vec<HostVuln>, ShodanError>:
+                Ok(Vec<HostVuln>): Returns every CVE of the host with its ports and details, riskiest first.
+                Err(ShodanError): Returns an error if a CVEDB lookup fails for another reason than an unknown CVE.
+    */
+    let mut vulns = Vec::new();
+    for (cve_id, ports) in host.cve_ports() {
+        limiter.wait();
+        let details = match get_cve(&cve_id) {
+            Ok(cve) => Some(cve),
+            Err(ShodanError::Api { status: StatusCode::NOT_FOUND, .. }) => None,
+            Err(err) => return Err(err),
+        };
+        vulns.push(HostVuln { cve_id, ports, details });
+    }
+
+    vulns.sort_by(|a, b| compare_risk(b, a));
+    Ok(vulns)
+}
+
+fn compare_risk(a: &HostVuln, b: &HostVuln) -> Ordering {
+    let key = |vuln: &HostVuln| {
+        vuln.details.as_ref().map(|cve| (cve.kev, cve.epss.unwrap_or(0.0), cve.cvss.unwrap_or(0.0)))
+    };
+    match (key(a), key(b)) {
+        (Some((a_kev, a_epss, a_cvss)), Some((b_kev, b_epss, b_cvss))) => a_kev
+            .cmp(&b_kev)
+            .then(a_epss.total_cmp(&b_epss))
+            .then(a_cvss.total_cmp(&b_cvss)),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => Ordering::Equal,
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::net::IpAddr;
+use serde_json::Value;
+use crate::credentials::ApiKey;
+use crate::error::ShodanError;
+use crate::helpers::{self, RateLimiter};
+use crate::models::{DnsRecord, DnsRecordType};
+use crate::shodan;
+
+// Limits of one (GET /dns/resolve) or (GET /dns/reverse) request, keeping the URL well under the usual 2000 characters
+const BATCH_MAX_ITEMS: usize = 100;
+const BATCH_MAX_CHARS: usize = 1500;
+
+/*
+    Description:
+        Iterator over every DNS record of a domain, paging through (GET /dns/domain/{domain}) 100 records at a time
+        and stopping when Shodan reports no "more" pages. Each page costs 1 query credit, see credits_used().
+        A failed page is yielded as an error and ends the iteration.
+*/
+pub struct DomainRecords<'a> {
+    api_key: &'a ApiKey,
+    domain: &'a str,
+    history: bool,
+    record_type: Option<DnsRecordType>,
+    next_page: Option<u32>,
+    buffer: VecDeque<DnsRecord>,
+    subdomains: BTreeSet<String>,
+    credits_used: u32,
+    limiter: RateLimiter,
+}
+
+impl<'a> DomainRecords<'a> {
+    pub fn new(api_key: &'a ApiKey, domain: &'a str, history: bool, record_type: Option<DnsRecordType>) -> Self {
+        /*
+            Description:
+                Prepares the iteration, no request is sent before the first call to next().
+            Parameters:
+                api_key (&ApiKey): Shodan API Key
+                domain (&str): Domain name to lookup; example "cnn.com"
+                history (bool): True if historical DNS data should be included in the results
+                record_type (Option<DnsRecordType>): Only return the records of this DNS type, None for every type
+        */
+        DomainRecords {
+            api_key,
+            domain,
+            history,
+            record_type,
+            next_page: Some(1),
+            buffer: VecDeque::new(),
+            subdomains: BTreeSet::new(),
+            credits_used: 0,
+            limiter: RateLimiter::default(),
+        }
+    }
+
+    pub fn credits_used(&self) -> u32 {
+        /*
+            Description:
+                Number of query credits spent so far (1 per page fetched).
+        */
+        self.credits_used
+    }
+
+    pub fn subdomains(&self) -> &BTreeSet<String> {
+        /*
+            Description:
+                Subdomains reported by the pages fetched so far, sorted and without duplicates.
+        */
+        &self.subdomains
+    }
+
+    fn fetch_next_page(&mut self) -> Result<(), ShodanError> {
+        let page = match self.next_page {
+            Some(page) => page,
+            None => return Ok(()),
+        };
+
+        self.limiter.wait();
+        self.next_page = None;
+        let info = shodan::get_dns_domain(self.domain, self.history, self.record_type.as_ref(), page, self.api_key)?;
+        self.credits_used += 1;
+
+        self.subdomains.extend(info.subdomains);
+        if info.more && !info.data.is_empty() {
+            self.next_page = Some(page + 1);
+        }
+        self.buffer.extend(info.data);
+        Ok(())
+    }
+}
+
+impl Iterator for DomainRecords<'_> {
+    type Item = Result<DnsRecord, ShodanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.buffer.is_empty() && self.next_page.is_some() {
+            if let Err(err) = self.fetch_next_page() {
+                return Some(Err(err));
+            }
+        }
+        self.buffer.pop_front().map(Ok)
+    }
+}
+
+pub fn resolve_hostnames<I, S>(api_key: &ApiKey, hostnames: I, limiter: &mut RateLimiter) -> Result<HashMap<String, Option<IpAddr>>, ShodanError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    /*
+        Description:
+            DNS Lookup for any number of hostnames
+            Splits the hostnames into batches small enough for (GET /dns/resolve), sends them under the rate limiter
+            and merges the answers.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            hostnames (I): The hostnames to resolve (any iterator of &str/ String), duplicates are only sent once.
+            limiter (&mut RateLimiter): Rate limiter shared with the other calls of the program.
+        Returns:
+            Result<HashMap<String, Option<IpAddr>>, ShodanError>:
+                Ok(HashMap<String, Option<IpAddr>>): Returns the IP of every hostname, None if it does not resolve.
+                Err(ShodanError): Returns an error if one of the batches fails.
+    */
+    let hostnames: BTreeSet<String> = hostnames.into_iter().map(|hostname| hostname.as_ref().to_string()).collect();
+    let mut resolved = HashMap::new();
+
+    for batch in batches(hostnames.iter().map(String::as_str)) {
+        limiter.wait();
+        let answers = helpers::json_from_response(shodan::get_dns_resolve(&batch.join(","), api_key))?;
+        for hostname in batch {
+            let ip = answers
+                .get(hostname)
+                .and_then(Value::as_str)
+                .and_then(|ip| ip.parse::<IpAddr>().ok());
+            resolved.insert(hostname.to_string(), ip);
+        }
+    }
+
+    Ok(resolved)
+}
+
+pub fn reverse_ips<I>(api_key: &ApiKey, ips: I, limiter: &mut RateLimiter) -> Result<HashMap<IpAddr, Vec<String>>, ShodanError>
+where
+    I: IntoIterator<Item = IpAddr>,
+{
+    /*
+        Description:
+            Reverse DNS Lookup for any number of IPs
+            Splits the IPs into batches small enough for (GET /dns/reverse), sends them under the rate limiter
+            and merges the answers.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            ips (I): The IPs to look up, duplicates are only sent once.
+            limiter (&mut RateLimiter): Rate limiter shared with the other calls of the program.
+        Returns:
+            Result<HashMap<IpAddr, Vec<String>>, ShodanError>:
+                Ok(HashMap<IpAddr, Vec<String>>): Returns the hostnames of every IP, empty if none is defined.
+                Err(ShodanError): Returns an error if one of the batches fails.
+    */
+    let ips: BTreeSet<String> = ips.into_iter().map(|ip| ip.to_string()).collect();
+    let mut reversed = HashMap::new();
+
+    for batch in batches(ips.iter().map(String::as_str)) {
+        limiter.wait();
+        let answers = helpers::json_from_response(shodan::get_dns_reverse(&batch.join(","), api_key))?;
+        for ip in batch {
+            let hostnames = answers
+                .get(ip)
+                .and_then(Value::as_array)
+                .map(|hostnames| hostnames.iter().filter_map(Value::as_str).map(String::from).collect())
+                .unwrap_or_default();
+            if let Ok(ip) = ip.parse::<IpAddr>() {
+                reversed.insert(ip, hostnames);
+            }
+        }
+    }
+
+    Ok(reversed)
+}
+
+fn batches<'a>(items: impl Iterator<Item = &'a str>) -> Vec<Vec<&'a str>> {
+    let mut batches = Vec::new();
+    let mut batch: Vec<&str> = Vec::new();
+    let mut chars = 0;
+
+    for item in items {
+        if !batch.is_empty() && (batch.len() == BATCH_MAX_ITEMS || chars + item.len() + 1 > BATCH_MAX_CHARS) {
+            batches.push(std::mem::take(&mut batch));
+            chars = 0;
+        }
+        chars += item.len() + 1;
+        batch.push(item);
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+
+    batches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batches_stop_at_the_item_limit() {
+        let hostnames: Vec<String> = (0..250).map(|index| format!("h{}.io", index)).collect();
+        let sizes: Vec<usize> = batches(hostnames.iter().map(String::as_str)).iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![BATCH_MAX_ITEMS, BATCH_MAX_ITEMS, 50]);
+    }
+
+    #[test]
+    fn batches_stop_at_the_character_limit() {
+        // 99 characters plus the "," separator: 15 hostnames fill the 1500 characters of a batch
+        let hostnames: Vec<String> = (0..20).map(|index| format!("{:0>92}.com.io", index)).collect();
+        assert!(hostnames.iter().all(|hostname| hostname.len() == 99));
+        let batched = batches(hostnames.iter().map(String::as_str));
+        assert_eq!(batched.iter().map(Vec::len).collect::<Vec<_>>(), vec![15, 5]);
+        assert!(batched.iter().all(|batch| batch.join(",").len() < BATCH_MAX_CHARS));
+    }
+
+    #[test]
+    fn batches_keep_every_item_once_in_order() {
+        assert!(batches(std::iter::empty()).is_empty());
+
+        let long = "x".repeat(BATCH_MAX_CHARS * 2);
+        let batched = batches(["a.io", long.as_str(), "b.io"].into_iter());
+        assert_eq!(batched, vec![vec!["a.io"], vec![long.as_str()], vec!["b.io"]]);
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+use std::io;
+use reqwest::Error as ReqwestError;
+use reqwest::StatusCode;
+use crate::credentials;
+use crate::plans::{Capability, PlanTier};
+
+/*
+    Description:
+        Error type returned by the higher-level helpers built on top of the raw Shodan functions.
+        The raw functions in shodan.rs keep returning ReqwestError, this type is used as soon as we need to decode a body,
+        check the status of a response or read/ write a file.
+    Variants:
+        Http(ReqwestError): The request itself failed (network, TLS, timeout...), its URL is stored without the API key.
+        Api { status, message }: Shodan answered with a non-success status, message is the "error" field of the body when there is one.
+        Json(serde_json::Error): The body (or a local file) could not be decoded/ encoded as JSON.
+        Io(io::Error): Reading or writing a local file failed.
+        Invalid(String): The data we got back (or were given) does not make sense for the operation.
+        PlanRequired { capability, required, plan }: Refused locally, the plan of the API key does not include capability.
+*/
+#[derive(Debug)]
+pub enum ShodanError {
+    Http(ReqwestError),
+    Api { status: StatusCode, message: String },
+    Json(serde_json::Error),
+    Io(io::Error),
+    Invalid(String),
+    PlanRequired { capability: Capability, required: PlanTier, plan: String },
+}
+
+impl fmt::Display for ShodanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShodanError::Http(err) => write!(f, "HTTP error: {}", err),
+            ShodanError::Api { status, message } => write!(f, "Shodan API error ({}): {}", status, message),
+            ShodanError::Json(err) => write!(f, "JSON error: {}", err),
+            ShodanError::Io(err) => write!(f, "IO error: {}", err),
+            ShodanError::Invalid(message) => write!(f, "Invalid data: {}", message),
+            ShodanError::PlanRequired { capability, required, plan } => {
+                write!(f, "Plan too limited: {} requires the {} plan or higher (current plan: \"{}\")", capability, required, plan)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShodanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ShodanError::Http(err) => Some(err),
+            ShodanError::Json(err) => Some(err),
+            ShodanError::Io(err) => Some(err),
+            ShodanError::Api { .. } | ShodanError::Invalid(_) | ShodanError::PlanRequired { .. } => None,
+        }
+    }
+}
+
+impl From<ReqwestError> for ShodanError {
+    fn from(err: ReqwestError) -> Self {
+        ShodanError::Http(credentials::redact_error(err))
+    }
+}
+
+impl From<serde_json::Error> for ShodanError {
+    fn from(err: serde_json::Error) -> Self {
+        ShodanError::Json(err)
+    }
+}
+
+impl From<io::Error> for ShodanError {
+    fn from(err: io::Error) -> Self {
+        ShodanError::Io(credentials::redact_io_error(err))
+    }
+}
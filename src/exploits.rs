@@ -0,0 +1,117 @@
+use std::collections::{BTreeMap, HashMap};
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::credentials::ApiKey;
+use crate::error::ShodanError;
+use crate::helpers::{self, RateLimiter};
+use crate::models::{FacetValue, HostInfo};
+
+/*
+    Description:
+        Exploit as returned by the Exploits API (https://exploits.shodan.io). source is the database it comes from
+        (ex: "ExploitDB", "Metasploit"), exploit_type its kind (ex: "remote", "dos", "webapps").
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exploit {
+    #[serde(rename = "_id", default)]
+    pub id: Value,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub cve: Vec<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub platform: Option<String>,
+    #[serde(rename = "type", default)]
+    pub exploit_type: Option<String>,
+    #[serde(default)]
+    pub date: Option<String>,
+}
+
+/*
+    Description:
+        Result of search_exploits() and count_exploits(): the matching exploits (always empty for count_exploits()),
+        the total number of matches and the requested facets.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExploitSearch {
+    #[serde(default)]
+    pub matches: Vec<Exploit>,
+    #[serde(default)]
+    pub total: u64,
+    #[serde(default)]
+    pub facets: HashMap<String, Vec<FacetValue>>,
+}
+
+pub fn search_exploits(api_key: &ApiKey, query: &str, facets: &str, page: u32) -> Result<ExploitSearch, ShodanError> {
+    /*
+        Description:
+            Search for Exploits
+            Search across a variety of data sources for exploits and use facets to get summary information.
+            (GET https://exploits.shodan.io/api/search)
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            query (&str): Search query, with optional filters (ex: "cve:CVE-2021-44228", "platform:php type:webapps")
+            facets (&str): A comma-separated list of properties to get summary information on (ex: "source,type:5"), "" for none
+            page (u32): The page number to page through results 100 at a time, starting at 1
+        Returns:
+            Result<ExploitSearch, ShodanError>:
+                Ok(ExploitSearch): Returns the matching exploits and facets, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
+    */
+    let client = Client::new();
+    let request = client
+        .get("https://exploits.shodan.io/api/search")
+        .query(&[("query", query), ("facets", facets), ("page", &page.to_string()), ("key", api_key.expose())]);
+    let response = helpers::send("search_exploits", request);
+    helpers::decode_response(response)
+}
+
+pub fn count_exploits(api_key: &ApiKey, query: &str, facets: &str) -> Result<ExploitSearch, ShodanError> {
+    /*
+        Description:
+            Count Exploits
+            Same as search_exploits() but only returns the total number of matches and the facets.
+            (GET https://exploits.shodan.io/api/count)
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            query (&str): Search query, with optional filters (ex: "cve:CVE-2021-44228", "platform:php type:webapps")
+            facets (&str): A comma-separated list of properties to get summary information on (ex: "source,type:5"), "" for none
+        Returns:
+            Result<ExploitSearch, ShodanError>:
+                Ok(ExploitSearch): Returns the total and facets, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
+    */
+    let client = Client::new();
+    let request = client
+        .get("https://exploits.shodan.io/api/count")
+        .query(&[("query", query), ("facets", facets), ("key", api_key.expose())]);
+    let response = helpers::send("count_exploits", request);
+    helpers::decode_response(response)
+}
+
+pub fn get_host_exploits(api_key: &ApiKey, host: &HostInfo, limiter: &mut RateLimiter) -> Result<BTreeMap<String, Vec<Exploit>>, ShodanError> {
+    /*
+        Description:
+            Known exploits of a host
+            Searches the Exploits API for every CVE of a host (host level "vulns" and the "vulns" of every banner).
+            Only the first page (100 exploits) is kept for each CVE.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            host (&HostInfo): Host decoded from (GET /shodan/host/{ip}).
+            limiter (&mut RateLimiter): Rate limiter shared with the other calls of the program.
+        Returns:
+            Result<BTreeMap<String, Vec<Exploit>>, ShodanError>:
+                Ok(BTreeMap<String, Vec<Exploit>>): Returns the known exploits by CVE ID (empty when there is none).
+                Err(ShodanError): Returns an error if one of the searches fails.
+    */
+    let mut exploits = BTreeMap::new();
+    for cve_id in host.cve_ports().into_keys() {
+        limiter.wait();
+        let search = search_exploits(api_key, &format!("cve:{}", cve_id), "", 1)?;
+        exploits.insert(cve_id, search.matches);
+    }
+    Ok(exploits)
+}
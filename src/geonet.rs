@@ -0,0 +1,216 @@
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use crate::error::ShodanError;
+use crate::helpers;
+use crate::models::{DnsRecordType, HostInfo};
+
+/*
+    Description:
+        Geonet vantage point a check was run from (ex: {"city": "Frankfurt am Main", "country": "DE", "latlon": "50.1155,8.6842"}).
+*/
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GeoLocation {
+    #[serde(default)]
+    pub city: Option<String>,
+    #[serde(default)]
+    pub country: Option<String>,
+    #[serde(default)]
+    pub latlon: Option<String>,
+}
+
+/*
+    Description:
+        Result of an ICMP ping from one vantage point, the round-trip times are in milliseconds.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PingResult {
+    pub ip: String,
+    #[serde(default)]
+    pub is_alive: bool,
+    #[serde(default)]
+    pub min_rtt: f64,
+    #[serde(default)]
+    pub avg_rtt: f64,
+    #[serde(default)]
+    pub max_rtt: f64,
+    #[serde(default)]
+    pub rtts: Vec<f64>,
+    #[serde(default)]
+    pub packets_sent: u32,
+    #[serde(default)]
+    pub packets_received: u32,
+    #[serde(default)]
+    pub packet_loss: f64,
+    #[serde(default)]
+    pub from_loc: GeoLocation,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsAnswer {
+    #[serde(rename = "type")]
+    pub record_type: DnsRecordType,
+    pub value: String,
+}
+
+/*
+    Description:
+        Answers of a DNS lookup from one vantage point (empty when the name does not resolve there).
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsLookupResult {
+    #[serde(default)]
+    pub answers: Vec<DnsAnswer>,
+    #[serde(default)]
+    pub from_loc: GeoLocation,
+}
+
+/*
+    Description:
+        What the pings of a host tell about the services Shodan found on it:
+            Reachable: every vantage point got an answer.
+            RegionallyFiltered: some vantage points got an answer and others did not, the host is most likely firewalled by region.
+            Unreachable: no vantage point got an answer, the host is down (or drops ICMP everywhere).
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ReachabilityStatus {
+    Reachable,
+    RegionallyFiltered,
+    Unreachable,
+}
+
+/*
+    Description:
+        Host information joined with the Geonet pings of its IP, see join_reachability().
+*/
+#[derive(Debug, Clone, Serialize)]
+pub struct HostReachability {
+    pub ip: String,
+    pub ports: Vec<u16>,
+    pub status: ReachabilityStatus,
+    pub reachable_from: Vec<GeoLocation>,
+    pub unreachable_from: Vec<GeoLocation>,
+}
+
+pub fn ping(ip: &str) -> Result<PingResult, ShodanError> {
+    /*
+        Description:
+            Ping
+            Pings an IP from the Geonet vantage point closest to the Shodan servers. No API key needed.
+            (GET https://geonet.shodan.io/api/ping/{ip})
+        Parameters:
+            ip (&str): The IP to ping
+        Returns:
+            Result<PingResult, ShodanError>:
+                Ok(PingResult): Returns the result of the ping, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
+    */
+    let client = Client::new();
+    let url = format!("https://geonet.shodan.io/api/ping/{}", ip);
+    let response = helpers::send("ping", client.get(&url));
+    helpers::decode_response(response)
+}
+
+pub fn geoping(ip: &str) -> Result<Vec<PingResult>, ShodanError> {
+    /*
+        Description:
+            Ping from all locations
+            Pings an IP from every Geonet vantage point. No API key needed.
+            (GET https://geonet.shodan.io/api/geoping/{ip})
+        Parameters:
+            ip (&str): The IP to ping
+        Returns:
+            Result<Vec<PingResult>, ShodanError>:
+                Ok(Vec<PingResult>): Returns one result per vantage point, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
+    */
+    let client = Client::new();
+    let url = format!("https://geonet.shodan.io/api/geoping/{}", ip);
+    let response = helpers::send("geoping", client.get(&url));
+    helpers::decode_response(response)
+}
+
+pub fn dns_lookup(hostname: &str, record_type: &DnsRecordType) -> Result<DnsLookupResult, ShodanError> {
+    /*
+        Description:
+            DNS Lookup
+            Resolves a hostname from the Geonet vantage point closest to the Shodan servers. No API key needed.
+            (GET https://geonet.shodan.io/api/dns/{hostname})
+        Parameters:
+            hostname (&str): The hostname to resolve; example "google.com"
+            record_type (&DnsRecordType): The type of record to ask for (ex: DnsRecordType::A)
+        Returns:
+            Result<DnsLookupResult, ShodanError>:
+                Ok(DnsLookupResult): Returns the answers, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
+    */
+    let client = Client::new();
+    let url = format!("https://geonet.shodan.io/api/dns/{}", hostname);
+    let response = helpers::send("dns_lookup", client.get(&url).query(&[("rtype", record_type.as_str())]));
+    helpers::decode_response(response)
+}
+
+pub fn geodns_lookup(hostname: &str, record_type: &DnsRecordType) -> Result<Vec<DnsLookupResult>, ShodanError> {
+    /*
+        Description:
+            DNS Lookup from all locations
+            Resolves a hostname from every Geonet vantage point, to spot geo DNS or regional poisoning. No API key needed.
+            (GET https://geonet.shodan.io/api/geodns/{hostname})
+        Parameters:
+            hostname (&str): The hostname to resolve; example "google.com"
+            record_type (&DnsRecordType): The type of record to ask for (ex: DnsRecordType::A)
+        Returns:
+            Result<Vec<DnsLookupResult>, ShodanError>:
+                Ok(Vec<DnsLookupResult>): Returns the answers of every vantage point, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
+    */
+    let client = Client::new();
+    let url = format!("https://geonet.shodan.io/api/geodns/{}", hostname);
+    let response = helpers::send("geodns_lookup", client.get(&url).query(&[("rtype", record_type.as_str())]));
+    helpers::decode_response(response)
+}
+
+pub fn join_reachability(host: &HostInfo, pings: &[PingResult]) -> HostReachability {
+    /*
+        Description:
+            Joins a host decoded from (GET /shodan/host/{ip}) with the pings of its IP (see geoping()), to tell a service that
+            is down from one that is firewalled by region. Pings of other IPs are ignored.
+        Parameters:
+            host (&HostInfo): Host decoded from (GET /shodan/host/{ip}).
+            pings (&[PingResult]): Pings returned by geoping() or ping().
+        Returns:
+            HostReachability: The ports Shodan found on the host and where they can be reached from.
+    */
+    let (reachable, unreachable): (Vec<&PingResult>, Vec<&PingResult>) = pings
+        .iter()
+        .filter(|ping| ping.ip == host.ip_str)
+        .partition(|ping| ping.is_alive);
+
+    let status = match (reachable.is_empty(), unreachable.is_empty()) {
+        (false, true) => ReachabilityStatus::Reachable,
+        (false, false) => ReachabilityStatus::RegionallyFiltered,
+        (true, _) => ReachabilityStatus::Unreachable,
+    };
+
+    HostReachability {
+        ip: host.ip_str.clone(),
+        ports: host.ports.clone(),
+        status,
+        reachable_from: reachable.into_iter().map(|ping| ping.from_loc.clone()).collect(),
+        unreachable_from: unreachable.into_iter().map(|ping| ping.from_loc.clone()).collect(),
+    }
+}
+
+pub fn check_host_reachability(host: &HostInfo) -> Result<HostReachability, ShodanError> {
+    /*
+        Description:
+            Pings the IP of a host from every Geonet vantage point and joins the results with it, see join_reachability().
+        Parameters:
+            host (&HostInfo): Host decoded from (GET /shodan/host/{ip}).
+        Returns:
+            Result<HostReachability, ShodanError>:
+                Ok(HostReachability): Returns the ports of the host and where they can be reached from.
+                Err(ShodanError): Returns an error if the pings fail.
+    */
+    let pings = geoping(&host.ip_str)?;
+    Ok(join_reachability(host, &pings))
+}
@@ -1,7 +1,16 @@
+#[cfg(feature = "tracing")]
+use std::cell::Cell;
+use std::fmt::Debug;
 use std::fs;
 use std::io::{self, Read};
-use reqwest::blocking::Response;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use reqwest::blocking::{RequestBuilder, Response};
 use reqwest::Error as ReqwestError;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use crate::credentials;
+use crate::error::ShodanError;
 
 pub fn file_exists(path: &str) -> bool {
     /*
@@ -20,11 +29,13 @@ pub fn read_api_key(file_path: &str) -> Result<String, io::Error> {
     /*
     Description:
         Reads the content of a file and returns it as a String, representing an API key.
+        Surrounding whitespace (ex: the trailing newline of the file) is removed. See credentials::resolve_api_key()
+        to look for the key in every usual place and validate it.
     Parameters:
         file_path (&str): The path to the file containing the API key.
     Returns:
         Result<String, std::io::Error>: 
-            Ok(String): Returns the content of the file as a String.
+            Ok(String): Returns the content of the file, trimmed.
             Err(std::io::Error): Returns an error if reading the file fails.
     */
     if !file_exists(file_path) {
@@ -35,7 +46,106 @@ pub fn read_api_key(file_path: &str) -> Result<String, io::Error> {
     let mut content = String::new();
     file.read_to_string(&mut content)?;
 
-    Ok(content)
+    Ok(content.trim().to_string())
+}
+
+pub fn send(endpoint: &'static str, request: RequestBuilder) -> Result<Response, ReqwestError> {
+    /*
+    Description:
+        Sends a request built by one of the client modules (shodan.rs, cvedb.rs, geonet.rs...), the error being returned
+        without the API key (see credentials::redact_error()).
+        With the "tracing" feature, the request is sent inside a "shodan_request" span, followed by an event, both carrying
+        the endpoint, the HTTP method, the redacted URL, the status, the latency (until the response headers, the body
+        being read by the caller, ex: a stream), the retries (see set_request_retries()) and the credits used when known
+        (see send_with_credits()).
+    Parameters:
+        endpoint (&'static str): Name of the endpoint, the function sending the request (ex: "get_host_ip").
+        request (RequestBuilder): The request to send.
+    Returns:
+        Result<Response, ReqwestError>:
+            Ok(Response): Returns the response, whatever its status.
+            Err(ReqwestError): Returns an error if the request fails.
+    */
+    send_request(endpoint, request, None)
+}
+
+pub fn send_with_credits(endpoint: &'static str, request: RequestBuilder, credits_used: i64) -> Result<Response, ReqwestError> {
+    /*
+    Description:
+        Same as send(), for a request whose cost is known in advance (ex: a search with filters costs 1 query credit),
+        recorded as credits_used with the "tracing" feature.
+    */
+    send_request(endpoint, request, Some(credits_used))
+}
+
+pub fn set_request_retries(retries: u32) {
+    /*
+    Description:
+        Number of times the requests this thread sends next were already tried with other API keys, recorded as retries
+        with the "tracing" feature (see KeyPool::call()). Set it back to 0 once done, requests sent outside a key pool
+        are never retried.
+    */
+    #[cfg(feature = "tracing")]
+    RETRIES.with(|current| current.set(retries));
+    #[cfg(not(feature = "tracing"))]
+    let _ = retries;
+}
+
+#[cfg(feature = "tracing")]
+thread_local! {
+    // See set_request_retries()
+    static RETRIES: Cell<u32> = const { Cell::new(0) };
+}
+
+#[cfg(not(feature = "tracing"))]
+fn send_request(_endpoint: &'static str, request: RequestBuilder, _credits_used: Option<i64>) -> Result<Response, ReqwestError> {
+    request.send().map_err(credentials::redact_error)
+}
+
+#[cfg(feature = "tracing")]
+fn send_request(endpoint: &'static str, request: RequestBuilder, credits_used: Option<i64>) -> Result<Response, ReqwestError> {
+    // The request is cloned to read its method and URL, a streamed body can't be so they are left empty then
+    let (method, url) = match request.try_clone().map(RequestBuilder::build) {
+        Some(Ok(built)) => {
+            let mut url = built.url().clone();
+            credentials::redact_url(&mut url);
+            (built.method().to_string(), url.to_string())
+        }
+        _ => (String::new(), String::new()),
+    };
+    let retries = RETRIES.with(Cell::get);
+    let span = tracing::info_span!(
+        "shodan_request",
+        endpoint,
+        method = %method,
+        url = %url,
+        retries,
+        credits_used,
+        status = tracing::field::Empty,
+        latency_ms = tracing::field::Empty,
+    );
+    let _entered = span.enter();
+
+    let started = Instant::now();
+    let result = request.send().map_err(credentials::redact_error);
+    let latency_ms = started.elapsed().as_millis() as u64;
+    span.record("latency_ms", latency_ms);
+
+    match &result {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            span.record("status", status);
+            if response.status().is_success() {
+                tracing::info!(endpoint, method = %method, url = %url, retries, credits_used, status, latency_ms, "shodan request");
+            } else {
+                tracing::warn!(endpoint, method = %method, url = %url, retries, credits_used, status, latency_ms, "shodan request failed");
+            }
+        }
+        Err(err) => {
+            tracing::warn!(endpoint, method = %method, url = %url, retries, credits_used, latency_ms, error = %err, "shodan request failed")
+        }
+    }
+    result
 }
 
 pub fn handle_error_shodan(result: Result<Response, ReqwestError>) {
@@ -55,8 +165,205 @@ pub fn handle_error_shodan(result: Result<Response, ReqwestError>) {
             println!("Body:\n{}", body);
         }
         Err(err) => {
-            // Handle generic error
-            eprintln!("Error: {:?}", err);
+            // Handle generic error, its URL holds the API key
+            eprintln!("Error: {:?}", credentials::redact_error(err));
+        }
+    }
+}
+
+pub fn json_from_response(result: Result<Response, ReqwestError>) -> Result<Value, ShodanError> {
+    /*
+    Description:
+        Turns the result of a Shodan module function call into a decoded JSON body.
+        A non-success status is turned into ShodanError::Api, using the "error" (or "detail") field of the body when Shodan provides one.
+    Parameters:
+        result (Result<Response, ReqwestError>): The result of a Shodan module function call.
+    Returns:
+        Result<Value, ShodanError>:
+            Ok(Value): Returns the decoded JSON body.
+            Err(ShodanError): Returns an error if the request failed, Shodan returned an error status or the body is not JSON.
+    */
+    let response = result?;
+    let status = response.status();
+    let body = response.text()?;
+
+    if !status.is_success() {
+        let message = serde_json::from_str::<Value>(&body)
+            .ok()
+            .and_then(|value| value.get("error").or_else(|| value.get("detail")).and_then(Value::as_str).map(String::from))
+            .unwrap_or(body);
+        return Err(ShodanError::Api { status, message });
+    }
+
+    Ok(serde_json::from_str(&body)?)
+}
+
+pub fn decode_response<T: DeserializeOwned>(result: Result<Response, ReqwestError>) -> Result<T, ShodanError> {
+    /*
+    Description:
+        Same as json_from_response(), but decodes the body into a typed value (see models.rs).
+    Parameters:
+        result (Result<Response, ReqwestError>): The result of a Shodan module function call.
+    Returns:
+        Result<T, ShodanError>:
+            Ok(T): Returns the decoded body.
+            Err(ShodanError): Returns an error if the request failed, Shodan returned an error status or the body does not match T.
+    */
+    let body = json_from_response(result)?;
+    Ok(serde_json::from_value(body)?)
+}
+
+pub fn handle_result_shodan<T: Debug>(result: Result<T, ShodanError>) {
+    /*
+    Description:
+        Same idea as handle_error_shodan(), but for the helpers returning an already decoded value instead of a raw Response.
+    Parameters:
+        result (Result<T, ShodanError>): The result of a helper call, containing either the decoded value or an error.
+    Returns:
+        None: The function does not return a value. It prints the decoded value or the error.
+    */
+    match result {
+        Ok(value) => {
+            println!("{:#?}", value);
+        }
+        Err(err) => {
+            eprintln!("Error: {}", err);
         }
     }
-}
\ No newline at end of file
+}
+
+pub fn parse_timestamp(timestamp: &str) -> Option<u64> {
+    /*
+    Description:
+        Parses a timestamp as returned by Shodan (ex: "2024-03-05T12:00:00.000000") into seconds since the Unix epoch.
+        Shodan timestamps are UTC, the fractional seconds and any timezone suffix are ignored.
+    Parameters:
+        timestamp (&str): The timestamp to parse, the date and time can be separated by "T" or a space.
+    Returns:
+        Option<u64>: Returns the number of seconds since the Unix epoch, or None if the timestamp can't be parsed.
+    */
+    let date = timestamp.get(0..10)?;
+    let time = timestamp.get(11..19)?;
+
+    let mut date_parts = date.split('-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (date_parts.next()??, date_parts.next()??, date_parts.next()??);
+    let mut time_parts = time.split(':').map(|part| part.parse::<i64>().ok());
+    let (hour, minute, second) = (time_parts.next()??, time_parts.next()??, time_parts.next()??);
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    // Days since the Unix epoch for a date of the proleptic Gregorian calendar (years starting in March)
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    u64::try_from(days * 86400 + hour * 3600 + minute * 60 + second).ok()
+}
+
+pub fn unix_now() -> u64 {
+    /*
+    Description:
+        Returns the current time in seconds since the Unix epoch.
+    Parameters:
+        None
+    Returns:
+        u64: The current Unix timestamp (0 if the system clock is set before 1970).
+    */
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn csv_field(field: &str) -> String {
+    /*
+    Description:
+        Escapes one CSV field (RFC 4180): quoted when it contains a comma, a quote or a line break, quotes doubled.
+    Parameters:
+        field (&str): The raw value of the field.
+    Returns:
+        String: The field, ready to be joined with ",".
+    */
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/*
+    Description:
+        Minimal client side rate limiter, used by the bulk helpers to stay under the Shodan API rate limit (1 request per second).
+        Call wait() right before sending each request, it sleeps just long enough since the previous call.
+*/
+pub struct RateLimiter {
+    interval: Duration,
+    last: Option<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(interval: Duration) -> Self {
+        RateLimiter { interval, last: None }
+    }
+
+    pub fn wait(&mut self) {
+        if let Some(last) = self.last {
+            let elapsed = last.elapsed();
+            if elapsed < self.interval {
+                thread::sleep(self.interval - elapsed);
+            }
+        }
+        self.last = Some(Instant::now());
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        RateLimiter::new(Duration::from_secs(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_timestamp_handles_shodan_formats() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00"), Some(0));
+        assert_eq!(parse_timestamp("2024-03-05T12:00:00.000000"), Some(1709640000));
+        assert_eq!(parse_timestamp("2024-03-05 12:00:00+00:00"), Some(1709640000));
+    }
+
+    #[test]
+    fn parse_timestamp_handles_leap_days() {
+        assert_eq!(parse_timestamp("2024-02-29T00:00:00"), Some(1709164800));
+        assert_eq!(parse_timestamp("2000-02-29T23:59:59"), Some(951868799));
+        assert_eq!(parse_timestamp("2024-03-01T00:00:00"), Some(1709164800 + 86400));
+    }
+
+    #[test]
+    fn parse_timestamp_refuses_bad_input() {
+        assert_eq!(parse_timestamp(""), None);
+        assert_eq!(parse_timestamp("2024-03-05"), None);
+        assert_eq!(parse_timestamp("not a timestamp at all"), None);
+        assert_eq!(parse_timestamp("2024-13-05T12:00:00"), None);
+        assert_eq!(parse_timestamp("2024-03-00T12:00:00"), None);
+        assert_eq!(parse_timestamp("2024-03-05T24:00:00"), None);
+        assert_eq!(parse_timestamp("1969-12-31T23:59:59"), None);
+    }
+
+    #[test]
+    fn csv_field_quotes_only_when_needed() {
+        assert_eq!(csv_field("nginx"), "nginx");
+        assert_eq!(csv_field(""), "");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line\nbreak"), "\"line\nbreak\"");
+        assert_eq!(csv_field("carriage\rreturn"), "\"carriage\rreturn\"");
+    }
+}
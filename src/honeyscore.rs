@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use reqwest::StatusCode;
+use serde::Serialize;
+use crate::credentials::ApiKey;
+use crate::error::ShodanError;
+use crate::helpers::{self, RateLimiter};
+use crate::models::SearchResults;
+use crate::shodan;
+
+// Score above which Shodan itself reports a host as a likely honeypot
+pub const DEFAULT_HONEYSCORE_THRESHOLD: f64 = 0.5;
+
+/*
+    Description:
+        Honeypot probability of an IP, from 0.0 (not a honeypot) to 1.0 (is a honeypot).
+*/
+#[derive(Debug, Clone, Serialize)]
+pub struct HoneyScore {
+    pub ip: String,
+    pub score: f64,
+}
+
+impl HoneyScore {
+    pub fn is_honeypot(&self, threshold: f64) -> bool {
+        /*
+            Description:
+                True if the score is strictly above threshold (ex: DEFAULT_HONEYSCORE_THRESHOLD).
+        */
+        self.score > threshold
+    }
+}
+
+/*
+    Description:
+        Honeyscores already looked up, so an IP seen on several search results or pages only costs one request.
+        IPs Shodan could not score are remembered too (as None) and not asked again. The lookups are rate limited.
+*/
+pub struct HoneyscoreCache {
+    scores: HashMap<String, Option<f64>>,
+    limiter: RateLimiter,
+}
+
+impl HoneyscoreCache {
+    pub fn new(limiter: RateLimiter) -> Self {
+        HoneyscoreCache { scores: HashMap::new(), limiter }
+    }
+
+    pub fn score(&mut self, api_key: &ApiKey, ip: &str) -> Result<Option<HoneyScore>, ShodanError> {
+        /*
+            Description:
+                Returns the cached honeyscore of ip, or looks it up with get_honeyscore() on a cache miss.
+            Returns:
+                Result<Option<HoneyScore>, ShodanError>:
+                    Ok(Option<HoneyScore>): Returns the score, None if Shodan could not calculate it.
+                    Err(ShodanError): Returns an error if the GET request fails for another reason (ex: rate limited),
+                    nothing being cached so the IP is looked up again next time.
+        */
+        if let Some(score) = self.scores.get(ip) {
+            return Ok(score.map(|score| HoneyScore { ip: ip.to_string(), score }));
+        }
+
+        self.limiter.wait();
+        let score = match get_honeyscore(api_key, ip) {
+            Ok(honeyscore) => Some(honeyscore.score),
+            Err(err) if is_unscorable(&err) => None,
+            Err(err) => return Err(err),
+        };
+        self.scores.insert(ip.to_string(), score);
+        Ok(score.map(|score| HoneyScore { ip: ip.to_string(), score }))
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+}
+
+impl Default for HoneyscoreCache {
+    fn default() -> Self {
+        HoneyscoreCache::new(RateLimiter::default())
+    }
+}
+
+pub fn get_honeyscore(api_key: &ApiKey, ip: &str) -> Result<HoneyScore, ShodanError> {
+    /*
+        Description:
+            Calculate Honeypot Probability Score
+            Typed version of shodan::get_labs_honeyscore().
+            (GET /labs/honeyscore/{ip})
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            ip (&str): Host IP address
+        Returns:
+            Result<HoneyScore, ShodanError>:
+                Ok(HoneyScore): Returns the honeypot probability of the IP.
+                Err(ShodanError): Returns an error if the GET request fails or Shodan could not calculate the score.
+    */
+    let score: f64 = helpers::decode_response(shodan::get_labs_honeyscore(ip, api_key))?;
+    Ok(HoneyScore { ip: ip.to_string(), score })
+}
+
+pub fn drop_honeypots(api_key: &ApiKey, mut results: SearchResults, threshold: f64, cache: &mut HoneyscoreCache) -> Result<SearchResults, ShodanError> {
+    /*
+        Description:
+            Search post-filter
+            Removes from the matches of a search page every banner whose IP has a honeyscore above threshold.
+            Banners without an IP, or whose IP could not be scored, are kept. total and facets are left as returned by Shodan.
+            WARNING ! -> Sends one (GET /labs/honeyscore/{ip}) per IP not in the cache, rate limited by the cache.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            results (SearchResults): Search page decoded from (GET /shodan/host/search).
+            threshold (f64): Highest honeyscore kept (ex: DEFAULT_HONEYSCORE_THRESHOLD).
+            cache (&mut HoneyscoreCache): Scores shared between the pages / searches of the program.
+        Returns:
+            Result<SearchResults, ShodanError>:
+                Ok(SearchResults): Returns the search page without the likely honeypots.
+                Err(ShodanError): Returns an error if a honeyscore lookup fails.
+    */
+    let mut kept = Vec::with_capacity(results.matches.len());
+    for banner in results.matches {
+        let honeypot = match &banner.ip_str {
+            Some(ip) => cache.score(api_key, ip)?.is_some_and(|score| score.is_honeypot(threshold)),
+            None => false,
+        };
+        if !honeypot {
+            kept.push(banner);
+        }
+    }
+    results.matches = kept;
+    Ok(results)
+}
+
+fn is_unscorable(err: &ShodanError) -> bool {
+    // Shodan answers 404, or an error saying it is unable to calculate the honeyscore, for IPs it has no score for
+    match err {
+        ShodanError::Api { status: StatusCode::NOT_FOUND, .. } => true,
+        ShodanError::Api { message, .. } => message.to_ascii_lowercase().contains("calculate honeyscore"),
+        _ => false,
+    }
+}
@@ -0,0 +1,87 @@
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use crate::credentials::ApiKey;
+use crate::error::ShodanError;
+use crate::helpers;
+use crate::models::HostInfo;
+use crate::shodan;
+
+/*
+    Description:
+        Host summary returned by InternetDB (https://internetdb.shodan.io), the free and keyless Shodan lookup:
+        open ports, hostnames, CPEs, tags and CVE IDs, without any banner.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InternetDbRecord {
+    pub ip: String,
+    #[serde(default)]
+    pub ports: Vec<u16>,
+    #[serde(default)]
+    pub hostnames: Vec<String>,
+    #[serde(default)]
+    pub cpes: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub vulns: Vec<String>,
+}
+
+/*
+    Description:
+        Result of lookup_host(): either the InternetDB summary was enough, or the full host information was fetched.
+*/
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "source", content = "host", rename_all = "lowercase")]
+pub enum HostLookup {
+    InternetDb(InternetDbRecord),
+    Full(HostInfo),
+}
+
+pub fn get_internetdb_ip(ip: &str) -> Result<InternetDbRecord, ShodanError> {
+    /*
+        Description:
+            InternetDB Host Lookup
+            Returns the open ports, hostnames, CPEs, tags and vulnerabilities of an IP. No API key needed, no credit used.
+            (GET https://internetdb.shodan.io/{ip})
+        Parameters:
+            ip (&str): Host IP address
+        Returns:
+            Result<InternetDbRecord, ShodanError>:
+                Ok(InternetDbRecord): Returns the host summary, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or InternetDB has no information about the IP (404).
+    */
+    let client = Client::new();
+    let url = format!("https://internetdb.shodan.io/{}", ip);
+    let response = helpers::send("get_internetdb_ip", client.get(&url));
+    helpers::decode_response(response)
+}
+
+pub fn lookup_host<F>(ip: &str, api_key: &ApiKey, needs_detail: F) -> Result<HostLookup, ShodanError>
+where
+    F: Fn(&InternetDbRecord) -> bool,
+{
+    /*
+        Description:
+            InternetDB first host lookup
+            Tries the free InternetDB lookup first and only falls back to (GET /shodan/host/{ip}) when needs_detail says the
+            summary is not enough (ex: |record| !record.vulns.is_empty() to get the banners of vulnerable hosts only),
+            or when InternetDB has no information about the IP.
+        Parameters:
+            ip (&str): Host IP address
+            api_key (&ApiKey): Shodan API Key, only used for the fallback
+            needs_detail (F): Decides from the InternetDB summary whether the full host information is needed.
+        Returns:
+            Result<HostLookup, ShodanError>:
+                Ok(HostLookup): Returns the InternetDB summary or the full host information.
+                Err(ShodanError): Returns an error if the lookup that was needed fails.
+    */
+    match get_internetdb_ip(ip) {
+        Ok(record) if !needs_detail(&record) => Ok(HostLookup::InternetDb(record)),
+        Ok(_) | Err(ShodanError::Api { status: StatusCode::NOT_FOUND, .. }) => {
+            let host = helpers::decode_response(shodan::get_host_ip(ip, api_key))?;
+            Ok(HostLookup::Full(host))
+        }
+        Err(err) => Err(err),
+    }
+}
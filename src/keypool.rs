@@ -0,0 +1,479 @@
+use std::collections::BTreeMap;
+use std::thread;
+use std::time::{Duration, Instant};
+use reqwest::StatusCode;
+use serde::Serialize;
+use crate::credentials::{self, ApiKey};
+use crate::error::ShodanError;
+use crate::helpers::{self, RateLimiter};
+use crate::models::{ApiInfo, HostInfo};
+use crate::shodan;
+
+// How long a key that got rate limited is left aside before being used again
+const RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(5);
+
+/*
+    Description:
+        How KeyPool picks the key of the next request:
+            RoundRobin: each usable key in turn.
+            LeastUsed: the usable key that made the fewest requests through the pool.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    RoundRobin,
+    LeastUsed,
+}
+
+/*
+    Description:
+        Accounting of one key of a KeyPool, the key itself being redacted (see credentials::redact_api_key()).
+        The credit fields come from get_api_info() (see KeyPool::refresh_usage()) and are None until it was called,
+        the *_used fields being the credits spent since the first call.
+*/
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyUsage {
+    pub key: String,
+    pub requests: u64,
+    pub rate_limited: u64,
+    pub exhausted: bool,
+    pub plan: Option<String>,
+    pub query_credits: Option<i64>,
+    pub query_credits_used: Option<i64>,
+    pub scan_credits: Option<i64>,
+    pub scan_credits_used: Option<i64>,
+}
+
+struct PooledKey {
+    key: ApiKey,
+    limiter: RateLimiter,
+    requests: u64,
+    rate_limited: u64,
+    cooldown_until: Option<Instant>,
+    exhausted: bool,
+    first_info: Option<ApiInfo>,
+    info: Option<ApiInfo>,
+}
+
+enum KeyProblem {
+    RateLimited,
+    OutOfCredits,
+}
+
+/*
+    Description:
+        Pool of API keys spreading requests across them, so several memberships can share a large run (ex: enrich_hosts()).
+        Every key has its own rate limiter, so the pool sends up to one request per second per key.
+        A key that gets rate limited is left aside for a few seconds, a key out of credits is not used anymore.
+*/
+pub struct KeyPool {
+    keys: Vec<PooledKey>,
+    rotation: Rotation,
+    next: usize,
+}
+
+impl KeyPool {
+    pub fn new(keys: Vec<ApiKey>, rotation: Rotation) -> Result<Self, ShodanError> {
+        /*
+            Description:
+                Creates a pool from keys, duplicates being only kept once.
+            Returns:
+                Result<KeyPool, ShodanError>:
+                    Ok(KeyPool): Returns the pool.
+                    Err(ShodanError): Returns an error if keys is empty or one of them is not valid (see credentials::validate_api_key()).
+        */
+        let mut pooled: Vec<PooledKey> = Vec::new();
+        for key in keys {
+            credentials::validate_api_key(key.expose())?;
+            if pooled.iter().any(|pooled| pooled.key == key) {
+                continue;
+            }
+            pooled.push(PooledKey {
+                key,
+                limiter: RateLimiter::default(),
+                requests: 0,
+                rate_limited: 0,
+                cooldown_until: None,
+                exhausted: false,
+                first_info: None,
+                info: None,
+            });
+        }
+        if pooled.is_empty() {
+            return Err(ShodanError::Invalid("a key pool needs at least one API key".to_string()));
+        }
+        Ok(KeyPool { keys: pooled, rotation, next: 0 })
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn first_key(&self) -> &ApiKey {
+        /*
+            Description:
+                Returns the first key of the pool, for requests that must not be sent again with another key (ex: creating an alert).
+        */
+        &self.keys[0].key
+    }
+
+    pub fn call<T, F>(&mut self, mut request: F) -> Result<T, ShodanError>
+    where
+        F: FnMut(&ApiKey) -> Result<T, ShodanError>,
+    {
+        /*
+            Description:
+                Sends one request with the next key of the pool (ex: |api_key| helpers::decode_response(shodan::get_host_ip(ip, api_key))).
+                When the key is rate limited or out of credits, the request is sent again with another key, each key being tried once.
+                Waits for the end of a cooldown when every usable key is rate limited.
+                Only give it requests that can safely be sent twice (reads), as the retry may go to another account.
+            Parameters:
+                request (F): Sends the request with the API key it is given.
+            Returns:
+                Result<T, ShodanError>:
+                    Ok(T): Returns the result of the first key that was not rate limited or out of credits.
+                    Err(ShodanError): Returns the error of the request, or of the last key tried when none could be used.
+        */
+        self.call_pooled(|pooled| request(&pooled.key))
+    }
+
+    pub fn call_with_info<T, F>(&mut self, mut request: F) -> Result<T, ShodanError>
+    where
+        F: FnMut(&ApiKey, &ApiInfo) -> Result<T, ShodanError>,
+    {
+        /*
+            Description:
+                Same as call(), the request also being given the plan and credits of its key (ex: to check it with plans::require()).
+                get_api_info() (GET /api-info, no credits used) is only sent the first time a key is used, or after
+                refresh_usage(), the result being kept for the rest of the run.
+        */
+        self.call_pooled(|pooled| {
+            let info = match &pooled.info {
+                Some(info) => info.clone(),
+                None => {
+                    let info = shodan::get_api_info(&pooled.key)?;
+                    pooled.first_info.get_or_insert_with(|| info.clone());
+                    pooled.info = Some(info.clone());
+                    pooled.limiter.wait();
+                    info
+                }
+            };
+            request(&pooled.key, &info)
+        })
+    }
+
+    pub fn refresh_usage(&mut self) -> Result<(), ShodanError> {
+        /*
+            Description:
+                Updates the credits of every key with get_api_info() (GET /api-info, no credits used). The first call sets
+                the starting point of the credits used reported by usage(). A key without query or scan credits left is
+                not marked as exhausted, host lookups being free.
+            Returns:
+                Result<(), ShodanError>:
+                    Ok(()): Every key was updated.
+                    Err(ShodanError): Returns the first error, the keys before it being updated.
+        */
+        for pooled in &mut self.keys {
+            pooled.limiter.wait();
+            let info = shodan::get_api_info(&pooled.key)?;
+            if pooled.first_info.is_none() {
+                pooled.first_info = Some(info.clone());
+            }
+            #[cfg(feature = "tracing")]
+            if let Some(first) = &pooled.first_info {
+                tracing::info!(
+                    key = %pooled.key,
+                    plan = %info.plan,
+                    query_credits = info.query_credits,
+                    query_credits_used = first.query_credits - info.query_credits,
+                    scan_credits = info.scan_credits,
+                    scan_credits_used = first.scan_credits - info.scan_credits,
+                    "API key credits"
+                );
+            }
+            pooled.info = Some(info);
+        }
+        Ok(())
+    }
+
+    pub fn usage(&self) -> Vec<KeyUsage> {
+        /*
+            Description:
+                Returns the accounting of every key, in the order they were given.
+        */
+        self.keys
+            .iter()
+            .map(|pooled| {
+                let info = pooled.info.as_ref();
+                let first = pooled.first_info.as_ref();
+                KeyUsage {
+                    key: pooled.key.to_string(),
+                    requests: pooled.requests,
+                    rate_limited: pooled.rate_limited,
+                    exhausted: pooled.exhausted,
+                    plan: info.map(|info| info.plan.clone()),
+                    query_credits: info.map(|info| info.query_credits),
+                    query_credits_used: first.zip(info).map(|(first, info)| first.query_credits - info.query_credits),
+                    scan_credits: info.map(|info| info.scan_credits),
+                    scan_credits_used: first.zip(info).map(|(first, info)| first.scan_credits - info.scan_credits),
+                }
+            })
+            .collect()
+    }
+
+    pub fn total_query_credits(&self) -> Option<i64> {
+        /*
+            Description:
+                Query credits left on the keys that are not exhausted, as of the last refresh_usage(). None before the first one.
+        */
+        if self.keys.iter().any(|pooled| pooled.info.is_none()) {
+            return None;
+        }
+        Some(
+            self.keys
+                .iter()
+                .filter(|pooled| !pooled.exhausted)
+                .filter_map(|pooled| pooled.info.as_ref())
+                .map(|info| info.query_credits)
+                .sum(),
+        )
+    }
+
+    fn call_pooled<T, F>(&mut self, mut attempt: F) -> Result<T, ShodanError>
+    where
+        F: FnMut(&mut PooledKey) -> Result<T, ShodanError>,
+    {
+        // Tries attempt() with the keys picked one after the other, see call()
+        let mut tried: Vec<usize> = Vec::new();
+        let mut last_err = None;
+
+        while let Some(index) = self.pick(&tried) {
+            tried.push(index);
+            let pooled = &mut self.keys[index];
+            pooled.limiter.wait();
+            pooled.requests += 1;
+
+            // The requests sent by attempt() are recorded inside this span, with the number of keys tried before
+            #[cfg(feature = "tracing")]
+            let _attempt = tracing::info_span!("shodan_key_pool", key = %pooled.key, retries = tried.len() - 1).entered();
+
+            helpers::set_request_retries(tried.len() as u32 - 1);
+            let result = attempt(pooled);
+            helpers::set_request_retries(0);
+            let err = match result {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+            match key_problem(&err) {
+                Some(KeyProblem::RateLimited) => {
+                    pooled.rate_limited += 1;
+                    pooled.cooldown_until = Some(Instant::now() + RATE_LIMIT_COOLDOWN);
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(key = %pooled.key, cooldown_secs = RATE_LIMIT_COOLDOWN.as_secs(), "API key rate limited, trying another one");
+                }
+                Some(KeyProblem::OutOfCredits) => {
+                    pooled.exhausted = true;
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(key = %pooled.key, "API key out of credits, not used anymore");
+                }
+                None => return Err(err),
+            }
+            last_err = Some(err);
+        }
+
+        Err(last_err.unwrap_or_else(|| ShodanError::Invalid("every API key of the pool is out of credits".to_string())))
+    }
+
+    fn pick(&mut self, tried: &[usize]) -> Option<usize> {
+        // Next key to use among the ones not exhausted nor tried yet, waiting for a cooldown to end when they all are in one
+        let candidates: Vec<usize> = (0..self.keys.len())
+            .filter(|index| !self.keys[*index].exhausted && !tried.contains(index))
+            .collect();
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let now = Instant::now();
+        let ready = |index: &usize| self.keys[*index].cooldown_until.is_none_or(|until| until <= now);
+        if !candidates.iter().any(ready) {
+            if let Some(until) = candidates.iter().filter_map(|index| self.keys[*index].cooldown_until).min() {
+                thread::sleep(until.saturating_duration_since(now));
+            }
+            return self.pick(tried);
+        }
+        let ready: Vec<usize> = candidates.into_iter().filter(ready).collect();
+
+        let index = match self.rotation {
+            Rotation::RoundRobin => ready
+                .iter()
+                .copied()
+                .find(|index| *index >= self.next)
+                .unwrap_or(ready[0]),
+            Rotation::LeastUsed => ready
+                .iter()
+                .copied()
+                .min_by_key(|index| self.keys[*index].requests)
+                .unwrap_or(ready[0]),
+        };
+        self.next = index + 1;
+        Some(index)
+    }
+}
+
+pub fn enrich_hosts<I, S>(pool: &mut KeyPool, ips: I) -> Result<BTreeMap<String, Option<HostInfo>>, ShodanError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    /*
+        Description:
+            Host Information for any number of IPs
+            Looks up every IP with (GET /shodan/host/{ip}) through the pool, spreading the lookups across its keys.
+        Parameters:
+            pool (&mut KeyPool): The keys to use.
+            ips (I): The IPs to look up (any iterator of &str/ String), duplicates are only sent once.
+        Returns:
+            Result<BTreeMap<String, Option<HostInfo>>, ShodanError>:
+                Ok(BTreeMap<String, Option<HostInfo>>): Returns the information of every IP, None if Shodan has none.
+                Err(ShodanError): Returns an error if a lookup fails with every key of the pool.
+    */
+    let mut hosts = BTreeMap::new();
+    for ip in ips {
+        let ip = ip.as_ref();
+        if hosts.contains_key(ip) {
+            continue;
+        }
+        let host = match pool.call(|api_key| helpers::decode_response::<HostInfo>(shodan::get_host_ip(ip, api_key))) {
+            Ok(host) => Some(host),
+            Err(ShodanError::Api { status: StatusCode::NOT_FOUND, .. }) => None,
+            Err(err) => return Err(err),
+        };
+        hosts.insert(ip.to_string(), host);
+    }
+    Ok(hosts)
+}
+
+fn key_problem(err: &ShodanError) -> Option<KeyProblem> {
+    // Shodan answers 429 when rate limited, and 402/ 403 with an error mentioning credits when the plan has none left.
+    // Other errors mentioning credits (ex: a 401 about the plan) say nothing about the key running out
+    match err {
+        ShodanError::Api { status: StatusCode::TOO_MANY_REQUESTS, .. } => Some(KeyProblem::RateLimited),
+        ShodanError::Api { status: StatusCode::PAYMENT_REQUIRED | StatusCode::FORBIDDEN, message }
+            if message.to_ascii_lowercase().contains("credits") =>
+        {
+            Some(KeyProblem::OutOfCredits)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEYS: [&str; 3] = [
+        "AAAAaaaaAAAAaaaaAAAAaaaaAAAAaaaa",
+        "BBBBbbbbBBBBbbbbBBBBbbbbBBBBbbbb",
+        "CCCCccccCCCCccccCCCCccccCCCCcccc",
+    ];
+
+    fn pool(rotation: Rotation) -> KeyPool {
+        let mut pool = KeyPool::new(KEYS.iter().map(|key| ApiKey::new(*key)).collect(), rotation).unwrap();
+        for pooled in &mut pool.keys {
+            pooled.limiter = RateLimiter::new(Duration::ZERO);
+        }
+        pool
+    }
+
+    fn api_error(status: StatusCode, message: &str) -> ShodanError {
+        ShodanError::Api { status, message: message.to_string() }
+    }
+
+    #[test]
+    fn round_robin_picks_each_key_in_turn() {
+        let mut pool = pool(Rotation::RoundRobin);
+        let picked: Vec<usize> = (0..5).map(|_| pool.pick(&[]).unwrap()).collect();
+        assert_eq!(picked, vec![0, 1, 2, 0, 1]);
+        assert_eq!(pool.pick(&[2, 0]), Some(1));
+        assert_eq!(pool.pick(&[0, 1, 2]), None);
+    }
+
+    #[test]
+    fn round_robin_skips_exhausted_and_cooling_down_keys() {
+        let mut pool = pool(Rotation::RoundRobin);
+        pool.keys[1].exhausted = true;
+        pool.keys[2].cooldown_until = Some(Instant::now() + Duration::from_secs(60));
+        let picked: Vec<usize> = (0..3).map(|_| pool.pick(&[]).unwrap()).collect();
+        assert_eq!(picked, vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn least_used_picks_the_key_with_fewest_requests() {
+        let mut pool = pool(Rotation::LeastUsed);
+        pool.keys[0].requests = 4;
+        pool.keys[1].requests = 2;
+        pool.keys[2].requests = 3;
+        assert_eq!(pool.pick(&[]), Some(1));
+        assert_eq!(pool.pick(&[1]), Some(2));
+    }
+
+    #[test]
+    fn key_problems_are_told_by_status() {
+        assert!(matches!(key_problem(&api_error(StatusCode::TOO_MANY_REQUESTS, "Rate limit reached")), Some(KeyProblem::RateLimited)));
+        assert!(matches!(
+            key_problem(&api_error(StatusCode::PAYMENT_REQUIRED, "Insufficient query credits")),
+            Some(KeyProblem::OutOfCredits)
+        ));
+        assert!(matches!(
+            key_problem(&api_error(StatusCode::FORBIDDEN, "Not enough Credits left")),
+            Some(KeyProblem::OutOfCredits)
+        ));
+        assert!(key_problem(&api_error(StatusCode::UNAUTHORIZED, "This plan has no query credits")).is_none());
+        assert!(key_problem(&api_error(StatusCode::FORBIDDEN, "Access denied")).is_none());
+        assert!(key_problem(&ShodanError::Invalid("credits".to_string())).is_none());
+    }
+
+    #[test]
+    fn call_moves_on_from_a_key_out_of_credits() {
+        let mut pool = pool(Rotation::RoundRobin);
+        let result = pool.call(|key| {
+            if key.expose() == KEYS[0] {
+                Err(api_error(StatusCode::PAYMENT_REQUIRED, "Insufficient query credits"))
+            } else {
+                Ok(key.expose().to_string())
+            }
+        });
+        assert_eq!(result.unwrap(), KEYS[1]);
+        assert!(pool.keys[0].exhausted);
+
+        let usage = pool.usage();
+        assert_eq!(usage.iter().map(|usage| usage.requests).collect::<Vec<_>>(), vec![1, 1, 0]);
+        assert!(usage.iter().all(|usage| !usage.key.contains(&KEYS[0][8..24])));
+    }
+
+    #[test]
+    fn call_returns_other_errors_without_retrying() {
+        let mut pool = pool(Rotation::RoundRobin);
+        let mut calls = 0;
+        let result: Result<(), ShodanError> = pool.call(|_| {
+            calls += 1;
+            Err(api_error(StatusCode::NOT_FOUND, "No information available for that IP."))
+        });
+        assert!(matches!(result, Err(ShodanError::Api { status: StatusCode::NOT_FOUND, .. })));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn call_fails_when_every_key_is_exhausted() {
+        let mut pool = pool(Rotation::RoundRobin);
+        let result: Result<(), ShodanError> = pool.call(|_| Err(api_error(StatusCode::PAYMENT_REQUIRED, "Insufficient query credits")));
+        assert!(matches!(result, Err(ShodanError::Api { status: StatusCode::PAYMENT_REQUIRED, .. })));
+        assert!(pool.keys.iter().all(|pooled| pooled.exhausted));
+
+        let result: Result<(), ShodanError> = pool.call(|_| panic!("no key should be used"));
+        assert!(matches!(result, Err(ShodanError::Invalid(message)) if message.contains("every API key")));
+    }
+}
@@ -0,0 +1,21 @@
+pub mod shodan;
+pub mod helpers;
+pub mod error;
+pub mod credentials;
+pub mod keypool;
+pub mod alerts;
+pub mod models;
+pub mod notifiers;
+pub mod webhook;
+pub mod dns;
+pub mod surface;
+pub mod internetdb;
+pub mod cvedb;
+pub mod exploits;
+pub mod honeyscore;
+pub mod plans;
+pub mod trends;
+pub mod geonet;
+pub mod queries;
+pub mod watchlist;
+pub mod output;
@@ -1,258 +1,256 @@
-mod shodan;
-mod helpers;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::net::IpAddr;
+use std::process;
+use std::time::Duration;
+use clap::Parser;
+use serde::Serialize;
+use serde_json::Value;
+use sh3nr0n_rust_membership::credentials::ApiKey;
+use sh3nr0n_rust_membership::error::ShodanError;
+use sh3nr0n_rust_membership::helpers::{self, RateLimiter};
+use sh3nr0n_rust_membership::keypool::{self, KeyPool};
+use sh3nr0n_rust_membership::models::{ApiInfo, NotifierConfig};
+use sh3nr0n_rust_membership::output::{self, OutputFormat, StreamWriter};
+use sh3nr0n_rust_membership::plans;
+use sh3nr0n_rust_membership::queries::SavedQueries;
+use sh3nr0n_rust_membership::{alerts, credentials, dns, notifiers, shodan};
+
+mod cli;
+
+use cli::{AlertCommand, Cli, Command, DirectoryCommand, DnsCommand, NotifierCommand, RequestCommand, ScanCommand};
 
 fn main() {
-    let file_path = "YOUR_ABSOLUTE_PATH"; // Replace with your actual Shodan API key file path
-
-    let mut shodan_api_key = String::new(); // Creating globally our shodan_api_key string variable
+    let cli = Cli::parse();
+    if let Err(err) = run(cli) {
+        eprintln!("[-] {}", err);
+        process::exit(1);
+    }
+}
 
-     // Use the read_file_content function from helpers module
-    match helpers::read_api_key(file_path) {
-        Ok(content) => {
-            println!("[+] We found your Shodan API key => {}", content);
-            shodan_api_key = content.clone();
+fn run(cli: Cli) -> Result<(), ShodanError> {
+    /*
+        Description:
+            Runs the command line with the keys of --key-pool, or the one resolved by credentials::resolve_api_key().
+            Only reads made of a single request (see replayable()) go through KeyPool::call(), which sends them again with
+            another key when one is rate limited or out of credits. Every other command runs once with the first key,
+            so a write or a multi-step run (ex: alert renew) is never replayed on another key, or another account.
+            Searches and counts are given the plan of their key by KeyPool::call_with_info(), fetched once per key per run.
+    */
+    let keys = match &cli.key_pool {
+        Some(path) => credentials::read_api_keys(path)?,
+        None => vec![credentials::resolve_api_key(cli.key.as_deref(), cli.key_file.as_deref())?.key],
+    };
+    let mut pool = KeyPool::new(keys, cli.rotation)?;
+
+    let result = match cli.command {
+        Command::Stream { alert_id } => return stream(pool.first_key(), alert_id.as_deref(), cli.format, cli.fields),
+        Command::Hosts { ips } => {
+            pool.refresh_usage()?;
+            let hosts = keypool::enrich_hosts(&mut pool, ips.iter().map(IpAddr::to_string))?;
+            match pool.refresh_usage() {
+                Ok(()) => print_usage(&pool),
+                Err(err) => eprintln!("[-] Could not refresh the credits of the keys: {}", err),
+            }
+            to_json(hosts.into_values().flatten().collect::<Vec<_>>())?
         }
-        Err(err) => {
-            eprintln!("[-] Error reading file: {}", err);
+        Command::Request(command @ (RequestCommand::Count { .. } | RequestCommand::Search { .. })) => {
+            pool.call_with_info(|api_key, info| execute(api_key, Some(info), command.clone()))?
         }
-    }
-
-    // REST API (https://developer.shodan.io/api)
-    // Search Methods
-    let ip_to_query = "8.8.8.8"; 
-
-    println!("\nRust implementation of Shodan Search Methods REST API:\n[+] (GET /shodan/host/ip) Function currently being runned: get_host_ip()");
-    helpers::handle_error_shodan(shodan::get_host_ip(ip_to_query, &shodan_api_key)); 
-
-    let mut query = "port:22";
-    let mut facets = "org,os";
-
-    println!("\n[+] (GET /shodan/host/count) Function currently being runned: get_host_count()");
-    helpers::handle_error_shodan(shodan::get_host_count(&shodan_api_key, query, facets)); 
-
-    query = "product:nginx";
-    facets = "country";
-
-    println!("\n[+] (GET/shodan/host/search) Function currently being runned: get_host_search()");
-    helpers::handle_error_shodan(shodan::get_host_search(&shodan_api_key, query, facets)); 
-
-    println!("\n[+] (GET /shodan/host/search/facets) Function currently being runned: get_facets_list()");
-    helpers::handle_error_shodan(shodan::get_facets_list(&shodan_api_key));
-
-    println!("\n[+] (GET /shodan/host/search/filters) Function currently being runned: get_filters_list()");
-    helpers::handle_error_shodan(shodan::get_filters_list(&shodan_api_key));
-
-    query = "Raspbian port:22";
-
-    println!("\n[+] (GET /shodan/host/search/tokens) Function currently being runned: get_host_search_tokens()");
-    helpers::handle_error_shodan(shodan::get_host_search_tokens(&shodan_api_key, query));
-
-    println!("\n--------------------------------------------------");
-
-    // On-Demand Scanning
-    println!("\nRust implementation of Shodan On-Demand Scanning REST API:\n[+] (GET /shodan/ports) Function currently being runned: get_ports()");
-    helpers::handle_error_shodan(shodan::get_ports(&shodan_api_key));
-
-    println!("\n[+] (GET /shodan/protocols) Function currently being runned: get_protocols()");
-    helpers::handle_error_shodan(shodan::get_protocols(&shodan_api_key));
-
-    // For request_scan() => EITHER:  
-    // ips: [String] A comma-separated list of IPs or netblocks (in CIDR notation) that should get crawled.
-    // let ips_to_scan = "8.8.8.8,1.1.1.1";
-    
-    // OR
-
-    // ips: [String] A comma-separated list of IPs or netblocks (in CIDR notation) that should get crawled.
-    // service: [Array] A list of services that should get scanned, where a service is defined as a [port, protocol].
-    let ips_with_ports = r#"
-    {
-    "8.8.8.8": [
-        [53, "dns-udp"],
-        [443, "https"]
-    ],
-    "1.1.1.1": [
-        [80, "http"],
-        [443, "https"]
-    ]
-    }"#;
-
-    println!("\n[+] (POST /shodan/scan) Function currently being runned: create_scan()");
-    helpers::handle_error_shodan(shodan::create_scan(&shodan_api_key, ips_with_ports));
-
-    println!("\n[+] (GET /shodan/scans) Function currently being runned: get_scans()");
-    helpers::handle_error_shodan(shodan::get_scans(&shodan_api_key));  
-
-    let scan_id = "YOUR_SCAN_ID"; 
-    println!("\n[+] (GET /shodan/scan/id) Function currently being runned: get_scan_id()");
-    helpers::handle_error_shodan(shodan::get_scan_id(scan_id, &shodan_api_key));  
-
-    println!("\n--------------------------------------------------");
-
-    // Network Alerts
-    let name = "DNS Alert";
-    let ips = vec!["8.8.8.8", "1.1.1.1"];
-    let expires = 0;
-    println!("\nRust implementation of Shodan Network Alerts REST API:\n[+] (POST /shodan/alert) Function currently being runned: create_alert()");
-    helpers::handle_error_shodan(shodan::create_alert(&shodan_api_key, name, ips, expires)); 
-  
-    let alert_id = "YOUR_ALERT_ID";
-    println!("\n[+] (GET /shodan/alert/id/info) Function currently being runned: get_alert_info_id()");
-    helpers::handle_error_shodan(shodan::get_alert_info_id(alert_id, &shodan_api_key));   
-
-    let alert_id = "YOUR_ALERT_ID";
-    println!("\n[+] (DELETE /shodan/alert/id) Function currently being runned: delete_alert()");
-    helpers::handle_error_shodan(shodan::delete_alert(alert_id, &shodan_api_key));
-
-    let alert_id = "YOUR_ALERT_ID";
-    let ips = vec!["1.1.1.1", "8.8.8.8"];
-    println!("\n[+] (POST /shodan/alert/id) Function currently being runned: edit_alert()");
-    helpers::handle_error_shodan(shodan::edit_alert(alert_id, &shodan_api_key, ips));
-
-    println!("\n[+] (GET /shodan/alert/info) Function currently being runned: get_alert_info()");
-    helpers::handle_error_shodan(shodan::get_alert_info(&shodan_api_key));
-
-    println!("\n[+] (GET /shodan/alert/triggers) Function currently being runned: get_alert_triggers()");
-    helpers::handle_error_shodan(shodan::get_alert_triggers(&shodan_api_key));
-
-    let alert_id = "YOUR_ALERT_ID";
-    let trigger = "new_service,vulnerable";
-    println!("\n[+] (PUT/shodan/alert/id/trigger/trigger) Function currently being runned: add_trigger()");
-    helpers::handle_error_shodan(shodan::add_trigger(alert_id, trigger, &shodan_api_key));
-
-    let alert_id = "YOUR_ALERT_ID";
-    let trigger = "new_service,vulnerable";
-    println!("\n[+] (DELETE /shodan/alert/id/trigger/trigger) Function currently being runned: delete_trigger()");
-    helpers::handle_error_shodan(shodan::delete_trigger(alert_id, trigger, &shodan_api_key));
-
-    let alert_id = "YOUR_ALERT_ID";
-    let trigger = "new_service";
-    let service = "1.1.1.1:53";
-    println!("\n[+] (PUT /shodan/alert/id/trigger/trigger/ignore/service) Function currently being runned: add_whitelist()");
-    helpers::handle_error_shodan(shodan::add_whitelist(alert_id, trigger, service, &shodan_api_key));
-
-    let alert_id = "YOUR_ALERT_ID";
-    let trigger = "new_service";
-    let service = "1.1.1.1:53";
-    println!("\n[+] (DELETE /shodan/alert/id/trigger/trigger/ignore/service) Function currently being runned: delete_whitelist()");
-    helpers::handle_error_shodan(shodan::delete_whitelist(alert_id, trigger, service, &shodan_api_key));
-
-    let alert_id = "YOUR_ALERT_ID";
-    let notifier_id = "default";
-    println!("\n[+] (PUT /shodan/alert/id/notifier/notifier_id) Function currently being runned: add_notifier_alert()");
-    helpers::handle_error_shodan(shodan::add_notifier_alert(alert_id, notifier_id, &shodan_api_key));
-
-    let alert_id = "YOUR_ALERT_ID";
-    let notifier_id = "default";
-    println!("\n[+] (DELETE /shodan/alert/id/notifier/notifier_id) Function currently being runned: delete_notifier_alert()");
-    helpers::handle_error_shodan(shodan::delete_notifier_alert(alert_id, notifier_id, &shodan_api_key));
-
-    println!("\n--------------------------------------------------");
-    
-    // Notifiers
-    println!("\nRust implementation of Shodan Notifiers REST API:\n[+] (GET /notifier) Function currently being runned: get_notifier()");
-    helpers::handle_error_shodan(shodan::get_notifier(&shodan_api_key)); 
-
-    println!("\n[+] (GET /notifier/provider) Function currently being runned: get_notifier_provider()");
-    helpers::handle_error_shodan(shodan::get_notifier_provider(&shodan_api_key)); 
-
-    let provider = "email";
-    let description = "Email notifier";
-    let argument = "to";
-    let argument_value = "jmath@shodan.io";
-    println!("\n[+] (POST /notifier) Function currently being runned: create_notifier()");
-    helpers::handle_error_shodan(shodan::create_notifier(&shodan_api_key, provider, description, argument, argument_value)); 
- 
-    let notifier_id = "YOUR_NOTIFIER_ID";
-    println!("\n[+] (DELETE /notifier/id) Function currently being runned: delete_notifier()");
-    helpers::handle_error_shodan(shodan::delete_notifier(notifier_id, &shodan_api_key)); 
-
-    let notifier_id = "YOUR_NOTIFIER_ID";
-    println!("\n[+] (GET /notifier/id) Function currently being runned: get_notifier_info()");
-    helpers::handle_error_shodan(shodan::get_notifier_info(notifier_id, &shodan_api_key));
-
-    let notifier_id = "YOUR_NOTIFIER_ID";
-    let argument = "to";
-    let argument_value = "jmath@shodan.io";
-    println!("\n[+] (PUT /notifier/id) Function currently being runned: edit_notifier()");
-    helpers::handle_error_shodan(shodan::edit_notifier(notifier_id, &shodan_api_key, argument, argument_value));
-
-    println!("\n--------------------------------------------------");
-
-    // Directory Methods
-
-    // Check get_query(), possibility to edit / remove these three parameters
-    let page = "";
-    let sort = "votes"; 
-    let order = "asc";
-
-    println!("\nRust implementation of Shodan Directory Methods REST API:\n[+] (GET /shodan/query) Function currently being runned: get_query()");
-    helpers::handle_error_shodan(shodan::get_query(page, sort, order, &shodan_api_key));
-  
-    let query = "webcam";
-    let page = ""; // Check get_query_search(), possibility to edit / remove this parameter
-    println!("\n[+] (GET /shodan/query/search) Function currently being runned: get_query_search()");
-    helpers::handle_error_shodan(shodan::get_query_search(query, page, &shodan_api_key));
-    
-    let size = "10"; // Check get_query_tags(), possibility to edit / remove this parameter
-    println!("\n[+] (GET /shodan/query/tags) Function currently being runned: get_query_tags()");
-    helpers::handle_error_shodan(shodan::get_query_tags(size, &shodan_api_key));
-  
-    println!("\n--------------------------------------------------");
-
-    // Account Methods
-    println!("\nRust implementation of Shodan Account Methods REST API:\n[+] (GET /account/profile) Function currently being runned: get_account_profile()");
-    helpers::handle_error_shodan(shodan::get_account_profile(&shodan_api_key));
-
-    println!("\n--------------------------------------------------");
-
-    // DNS Methods
-    let domain = "google.com";
-
-    // Check get_dns_domain(), possibility to edit / remove these three parameters
-    let history = "False";
-    let type_ = "A";
-    let page = "";
-
-    println!("\nRust implementation of Shodan DNS Methods REST API:\n[+] (GET /dns/domain/domain) Function currently being runned: get_dns_domain()");
-    helpers::handle_error_shodan(shodan::get_dns_domain(domain, history, type_, page, &shodan_api_key));
-
-    let hostnames = "google.com,facebook.com";
-    println!("\n[+] (GET /dns/resolve) Function currently being runned: get_dns_resolve()");
-    helpers::handle_error_shodan(shodan::get_dns_resolve(hostnames, &shodan_api_key));
+        Command::Request(command) if replayable(&command) => pool.call(|api_key| execute(api_key, None, command.clone()))?,
+        Command::Request(command) => execute(pool.first_key(), None, command)?,
+    };
+    print!("{}", output::render(&result, cli.format, &cli.fields)?);
+    Ok(())
+}
 
-    let ips = "8.8.8.8,1.1.1.1";
-    println!("\n[+] (GET /dns/reverse) Function currently being runned: get_dns_reverse()");
-    helpers::handle_error_shodan(shodan::get_dns_reverse(ips, &shodan_api_key));
+fn replayable(command: &RequestCommand) -> bool {
+    // Idempotent reads sending a single request, that can safely be sent again with another key of the pool
+    matches!(
+        command,
+        RequestCommand::Host { .. }
+            | RequestCommand::Count { .. }
+            | RequestCommand::Search { .. }
+            | RequestCommand::Tokens { .. }
+            | RequestCommand::Account
+            | RequestCommand::Info
+            | RequestCommand::Myip
+    )
+}
 
-    println!("\n--------------------------------------------------");
+fn execute(api_key: &ApiKey, info: Option<&ApiInfo>, command: RequestCommand) -> Result<Value, ShodanError> {
+    /*
+        Description:
+            Runs one subcommand and returns its result as JSON, raw responses being decoded with helpers::json_from_response().
+            Searches and counts are first checked against info, the plan of the API key (see plans.rs), so a query the plan
+            can't run is refused without spending a request. info is fetched with get_api_info() when the caller has none.
+    */
+    let plan = |info: Option<&ApiInfo>| match info {
+        Some(info) => Ok(info.clone()),
+        None => shodan::get_api_info(api_key),
+    };
+    match command {
+        RequestCommand::Host { ip } => helpers::json_from_response(shodan::get_host_ip(&ip.to_string(), api_key)),
+        RequestCommand::Count { query, facets } => {
+            plans::require_for_query(&plan(info)?, &query)?;
+            helpers::json_from_response(shodan::get_host_count(api_key, &query, &facets))
+        }
+        RequestCommand::Search { query, facets, page } => {
+            plans::require_for_search(&plan(info)?, &query, page)?;
+            helpers::json_from_response(shodan::get_host_search(api_key, &query, &facets, page))
+        }
+        RequestCommand::Tokens { query } => helpers::json_from_response(shodan::get_host_search_tokens(api_key, &query)),
+        RequestCommand::Scan(command) => execute_scan(api_key, command),
+        RequestCommand::Alert(command) => execute_alert(api_key, command),
+        RequestCommand::Notifier(command) => execute_notifier(api_key, command),
+        RequestCommand::Directory(command) => execute_directory(api_key, command),
+        RequestCommand::Dns(command) => execute_dns(api_key, command),
+        RequestCommand::Account => to_json(shodan::get_account_profile(api_key)?),
+        RequestCommand::Info => to_json(shodan::get_api_info(api_key)?),
+        RequestCommand::Myip => helpers::json_from_response(shodan::get_tools_myip(api_key)),
+    }
+}
 
-    // Utility Methods
-    println!("\nRust implementation of Shodan Utility Methods REST API:\n[+] (GET /tools/httpheaders) Function currently being runned: get_tools_httpheaders()");
-    helpers::handle_error_shodan(shodan::get_tools_headers(&shodan_api_key));
+fn execute_scan(api_key: &ApiKey, command: ScanCommand) -> Result<Value, ShodanError> {
+    match command {
+        ScanCommand::Ports => helpers::json_from_response(shodan::get_ports(api_key)),
+        ScanCommand::Protocols => helpers::json_from_response(shodan::get_protocols(api_key)),
+        ScanCommand::Create { targets } => helpers::json_from_response(shodan::create_scan(api_key, &targets)),
+        ScanCommand::List => helpers::json_from_response(shodan::get_scans(api_key)),
+        ScanCommand::Status { id } => helpers::json_from_response(shodan::get_scan_id(&id, api_key)),
+    }
+}
 
-    println!("\n[+] (GET /tools/myip) Function currently being runned: get_tools_myip()");
-    helpers::handle_error_shodan(shodan::get_tools_myip(&shodan_api_key));
+fn execute_alert(api_key: &ApiKey, command: AlertCommand) -> Result<Value, ShodanError> {
+    match command {
+        AlertCommand::Create { name, ips, expires } => {
+            to_json(shodan::create_alert(api_key, &name, ips.iter().map(String::as_str).collect(), expires)?)
+        }
+        AlertCommand::List => to_json(shodan::get_alert_info(api_key)?),
+        AlertCommand::Info { id } => to_json(shodan::get_alert_info_id(&id, api_key)?),
+        AlertCommand::Delete { id } => helpers::json_from_response(shodan::delete_alert(&id, api_key)),
+        AlertCommand::Edit { id, ips } => to_json(shodan::edit_alert(&id, api_key, ips.iter().map(String::as_str).collect())?),
+        AlertCommand::Expiring { days } => {
+            to_json(alerts::get_alerts_expiring_within(api_key, Duration::from_secs(days * 24 * 3600))?)
+        }
+        AlertCommand::Renew { id, expires } => to_json(alerts::renew_alert(api_key, &id, expires)?),
+        AlertCommand::Triggers => helpers::json_from_response(shodan::get_alert_triggers(api_key)),
+        AlertCommand::AddTrigger { id, triggers } => {
+            alerts::validate_triggers(api_key, &triggers)?;
+            helpers::json_from_response(shodan::add_trigger(&id, &triggers, api_key))
+        }
+        AlertCommand::RemoveTrigger { id, triggers } => helpers::json_from_response(shodan::delete_trigger(&id, &triggers, api_key)),
+        AlertCommand::Whitelist { id } => to_json(alerts::get_whitelist(api_key, &id)?),
+        AlertCommand::Ignore { id, trigger, mut services, csv } => {
+            if let Some(csv) = csv {
+                services.extend(alerts::read_whitelist_csv(&csv)?);
+            }
+            to_json(alerts::bulk_add_whitelist(api_key, &id, &trigger, &services, &mut RateLimiter::default()))
+        }
+        AlertCommand::Unignore { id, trigger, mut services, csv } => {
+            if let Some(csv) = csv {
+                services.extend(alerts::read_whitelist_csv(&csv)?);
+            }
+            to_json(alerts::bulk_delete_whitelist(api_key, &id, &trigger, &services, &mut RateLimiter::default()))
+        }
+        AlertCommand::AddNotifier { id, notifier_id } => helpers::json_from_response(shodan::add_notifier_alert(&id, &notifier_id, api_key)),
+        AlertCommand::RemoveNotifier { id, notifier_id } => helpers::json_from_response(shodan::delete_notifier_alert(&id, &notifier_id, api_key)),
+        AlertCommand::Export { path } => {
+            let config = alerts::export_alert_config(api_key)?;
+            alerts::write_alert_config(&path, &config)?;
+            to_json(config)
+        }
+        AlertCommand::Import { path } => to_json(alerts::import_alert_config(api_key, &alerts::read_alert_config(&path)?)?),
+    }
+}
 
-    println!("\n--------------------------------------------------");
+fn execute_notifier(api_key: &ApiKey, command: NotifierCommand) -> Result<Value, ShodanError> {
+    match command {
+        NotifierCommand::List => to_json(shodan::get_notifier(api_key)?),
+        NotifierCommand::Providers => to_json(shodan::get_notifier_provider(api_key)?),
+        NotifierCommand::Create { provider, description, args } => {
+            let args: HashMap<String, String> = args.into_iter().collect();
+            let config = NotifierConfig::from_args(&provider, &args);
+            notifiers::validate_notifier_config(api_key, &config)?;
+            to_json(shodan::create_notifier(api_key, &description, &config)?)
+        }
+        NotifierCommand::Info { id } => to_json(shodan::get_notifier_info(&id, api_key)?),
+        NotifierCommand::Delete { id } => helpers::json_from_response(shodan::delete_notifier(&id, api_key)),
+        NotifierCommand::Edit { id, args } => {
+            let args: HashMap<String, String> = args.into_iter().collect();
+            to_json(shodan::edit_notifier(&id, api_key, &args)?)
+        }
+    }
+}
 
-    // API Status Methods
-    println!("\nRust implementation of Shodan API Status Methods REST API:\n[+] (GET /api-info) Function currently being runned: get_api_info()");
-    helpers::handle_error_shodan(shodan::get_api_info(&shodan_api_key));
+fn execute_directory(api_key: &ApiKey, command: DirectoryCommand) -> Result<Value, ShodanError> {
+    match command {
+        DirectoryCommand::List { all: true, sort, order, .. } => {
+            to_json(SavedQueries::directory(api_key, sort, order).collect::<Result<Vec<_>, _>>()?)
+        }
+        DirectoryCommand::List { page, sort, order, .. } => to_json(shodan::get_query(page, sort, order, api_key)?),
+        DirectoryCommand::Search { query, all: true, .. } => to_json(SavedQueries::search(api_key, &query).collect::<Result<Vec<_>, _>>()?),
+        DirectoryCommand::Search { query, page, .. } => to_json(shodan::get_query_search(&query, page, api_key)?),
+        DirectoryCommand::Tags { size } => to_json(shodan::get_query_tags(size, api_key)?),
+    }
+}
 
-    println!("\n--------------------------------------------------");
+fn execute_dns(api_key: &ApiKey, command: DnsCommand) -> Result<Value, ShodanError> {
+    match command {
+        DnsCommand::Domain { domain, history, record_type, all: true, .. } => {
+            to_json(dns::DomainRecords::new(api_key, &domain, history, record_type).collect::<Result<Vec<_>, _>>()?)
+        }
+        DnsCommand::Domain { domain, history, record_type, page, .. } => {
+            to_json(shodan::get_dns_domain(&domain, history, record_type.as_ref(), page, api_key)?)
+        }
+        DnsCommand::Resolve { hostnames } => to_json(dns::resolve_hostnames(api_key, hostnames, &mut RateLimiter::default())?),
+        DnsCommand::Reverse { ips } => to_json(dns::reverse_ips(api_key, ips, &mut RateLimiter::default())?),
+    }
+}
 
-    /* 
-        STREAMING API (https://developer.shodan.io/api/stream)
-        Network Alerts
-        These streams provide a Private Firehose that contain information about the networks that you're monitoring. 
-        Use the REST API to create/ delete/ update the list of networks that you want Shodan to monitor.
+fn stream(api_key: &ApiKey, alert_id: Option<&str>, format: OutputFormat, fields: Vec<String>) -> Result<(), ShodanError> {
+    /*
+        Description:
+            Prints the banners of a network alert stream as they come, until the connection is closed.
+            Refused locally when the plan of the API key has no network alerts (checked by shodan::get_all_network_alerts()).
     */
-    println!("\nRust implementation of Shodan Network Alerts Streaming API:\n[+] (GET /shodan/alert) Function currently being runned: get_all_network_alerts()");
-    helpers::handle_error_shodan(shodan::get_all_network_alerts(&shodan_api_key));
-    
-    let alert_id = "YOUR_ALERT_ID";
-    println!("\n[+] (GET /shodan/alert/id) Function currently being runned: get_all_network_alert_for_alert_id()");
-    helpers::handle_error_shodan(shodan::get_all_network_alert_for_alert_id(alert_id, &shodan_api_key));
+    let info = shodan::get_api_info(api_key)?;
+    let response = match alert_id {
+        Some(alert_id) => shodan::get_all_network_alert_for_alert_id(alert_id, api_key, &info)?,
+        None => shodan::get_all_network_alerts(api_key, &info)?,
+    };
+    if !response.status().is_success() {
+        return helpers::json_from_response(Ok(response)).map(|_| ());
+    }
+
+    let mut writer = StreamWriter::new(format, fields);
+    for line in BufReader::new(response).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let banner: Value = serde_json::from_str(&line)?;
+        print!("{}", writer.line(&banner)?);
+    }
+    Ok(())
+}
 
+fn print_usage(pool: &KeyPool) {
+    // Per-key accounting of a run, on stderr so the output can still be piped
+    for usage in pool.usage() {
+        eprintln!(
+            "[*] {} ({}): {} requests, {} rate limited, {} query credits used, {} left{}",
+            usage.key,
+            usage.plan.as_deref().unwrap_or("unknown plan"),
+            usage.requests,
+            usage.rate_limited,
+            usage.query_credits_used.unwrap_or_default(),
+            usage.query_credits.unwrap_or_default(),
+            if usage.exhausted { ", out of credits" } else { "" }
+        );
+    }
 }
 
+fn to_json<T: Serialize>(value: T) -> Result<Value, ShodanError> {
+    Ok(serde_json::to_value(value)?)
+}
@@ -0,0 +1,682 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::helpers;
+
+/*
+    Description:
+        Trigger that can be enabled on a network alert, as listed by (GET /shodan/alert/triggers).
+        Triggers unknown to this enum (added by Shodan after this crate was written) are kept in Other(String).
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum AlertTrigger {
+    Any,
+    IndustrialControlSystem,
+    InternetScanner,
+    Iot,
+    Malware,
+    NewService,
+    OpenDatabase,
+    SslExpired,
+    Uncommon,
+    UncommonPlus,
+    Vulnerable,
+    VulnerableUnverified,
+    Other(String),
+}
+
+impl AlertTrigger {
+    pub fn as_str(&self) -> &str {
+        match self {
+            AlertTrigger::Any => "any",
+            AlertTrigger::IndustrialControlSystem => "industrial_control_system",
+            AlertTrigger::InternetScanner => "internet_scanner",
+            AlertTrigger::Iot => "iot",
+            AlertTrigger::Malware => "malware",
+            AlertTrigger::NewService => "new_service",
+            AlertTrigger::OpenDatabase => "open_database",
+            AlertTrigger::SslExpired => "ssl_expired",
+            AlertTrigger::Uncommon => "uncommon",
+            AlertTrigger::UncommonPlus => "uncommon_plus",
+            AlertTrigger::Vulnerable => "vulnerable",
+            AlertTrigger::VulnerableUnverified => "vulnerable_unverified",
+            AlertTrigger::Other(name) => name,
+        }
+    }
+
+    pub fn join(triggers: &[AlertTrigger]) -> String {
+        /*
+            Description:
+                Builds the comma-separated list of trigger names expected in the URL of the trigger endpoints.
+            Parameters:
+                triggers (&[AlertTrigger]): The triggers to join.
+            Returns:
+                String: The trigger names separated by commas (ex: "new_service,vulnerable").
+        */
+        triggers.iter().map(AlertTrigger::as_str).collect::<Vec<&str>>().join(",")
+    }
+}
+
+impl From<&str> for AlertTrigger {
+    fn from(name: &str) -> Self {
+        match name {
+            "any" => AlertTrigger::Any,
+            "industrial_control_system" => AlertTrigger::IndustrialControlSystem,
+            "internet_scanner" => AlertTrigger::InternetScanner,
+            "iot" => AlertTrigger::Iot,
+            "malware" => AlertTrigger::Malware,
+            "new_service" => AlertTrigger::NewService,
+            "open_database" => AlertTrigger::OpenDatabase,
+            "ssl_expired" => AlertTrigger::SslExpired,
+            "uncommon" => AlertTrigger::Uncommon,
+            "uncommon_plus" => AlertTrigger::UncommonPlus,
+            "vulnerable" => AlertTrigger::Vulnerable,
+            "vulnerable_unverified" => AlertTrigger::VulnerableUnverified,
+            other => AlertTrigger::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for AlertTrigger {
+    fn from(name: String) -> Self {
+        AlertTrigger::from(name.as_str())
+    }
+}
+
+impl From<AlertTrigger> for String {
+    fn from(trigger: AlertTrigger) -> Self {
+        trigger.as_str().to_string()
+    }
+}
+
+impl fmt::Display for AlertTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/*
+    Description:
+        Network alert as returned by (GET /shodan/alert/info), (GET /shodan/alert/{id}/info), (POST /shodan/alert) and (POST /shodan/alert/{id}).
+        expires is the lifetime in seconds the alert was created with (0 = never expires),
+        expiration is the UTC timestamp at which Shodan will remove the alert (None = never expires).
+        triggers holds the enabled triggers only, with the services ignored for each of them.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub created: Option<String>,
+    #[serde(default)]
+    pub expires: i64,
+    #[serde(default)]
+    pub expiration: Option<String>,
+    #[serde(default)]
+    pub filters: AlertFilters,
+    #[serde(default)]
+    pub triggers: HashMap<AlertTrigger, TriggerRule>,
+    #[serde(default)]
+    pub notifiers: Vec<Notifier>,
+    #[serde(default)]
+    pub size: u64,
+}
+
+/*
+    Description:
+        Criteria an alert is triggered on. The only supported option at the moment is the "ip" filter,
+        a list of IPs or network ranges defined using CIDR notation.
+*/
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlertFilters {
+    #[serde(default)]
+    pub ip: Vec<String>,
+}
+
+/*
+    Description:
+        Settings of one enabled trigger of an alert, ignore lists the "ip:port" services whitelisted for it.
+*/
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TriggerRule {
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+/*
+    Description:
+        Notification service as returned by (GET /notifier), (GET /notifier/{id}) and in the notifiers of an alert.
+        args holds the provider arguments (ex: {"to": "jmath@shodan.io"} for the email provider), kept as JSON values
+        so an argument Shodan sends as a number or a boolean does not make the whole listing fail to decode.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notifier {
+    pub id: String,
+    pub provider: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub args: HashMap<String, Value>,
+}
+
+impl Notifier {
+    pub fn config(&self) -> NotifierConfig {
+        /*
+            Description:
+                Returns the typed provider configuration of the notifier, see NotifierConfig::from_args().
+                Arguments that are not strings are given as their JSON text (ex: 42 -> "42"), null ones are left out.
+        */
+        let args: HashMap<String, String> = self
+            .args
+            .iter()
+            .filter_map(|(name, value)| match value {
+                Value::Null => None,
+                Value::String(value) => Some((name.clone(), value.clone())),
+                value => Some((name.clone(), value.to_string())),
+            })
+            .collect();
+        NotifierConfig::from_args(&self.provider, &args)
+    }
+}
+
+/*
+    Description:
+        Notification provider as listed by (GET /notifier/provider), with the names of the arguments it requires.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotifierProvider {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub required: Vec<String>,
+}
+
+impl Alert {
+    pub fn expiration_timestamp(&self) -> Option<u64> {
+        /*
+            Description:
+                Returns the expiration of the alert in seconds since the Unix epoch, None if the alert never expires
+                (or if Shodan sent a timestamp we can't parse).
+        */
+        self.expiration.as_deref().and_then(helpers::parse_timestamp)
+    }
+}
+
+/*
+    Description:
+        Notification service to create with (POST /notifier), one variant per provider listed by (GET /notifier/provider)
+        with all the arguments that provider requires. Providers added by Shodan after this crate was written can be
+        created with Other, giving the provider name and its arguments by hand.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotifierConfig {
+    Email { to: String },
+    Gotify { url: String, token: String },
+    PagerDuty { routing_key: String },
+    Phone { to: String },
+    Slack { webhook_url: String },
+    Telegram { chat_id: String, token: String },
+    Webhook { url: String },
+    Other { provider: String, args: HashMap<String, String> },
+}
+
+impl NotifierConfig {
+    pub fn provider(&self) -> &str {
+        match self {
+            NotifierConfig::Email { .. } => "email",
+            NotifierConfig::Gotify { .. } => "gotify",
+            NotifierConfig::PagerDuty { .. } => "pagerduty",
+            NotifierConfig::Phone { .. } => "phone",
+            NotifierConfig::Slack { .. } => "slack",
+            NotifierConfig::Telegram { .. } => "telegram",
+            NotifierConfig::Webhook { .. } => "webhook",
+            NotifierConfig::Other { provider, .. } => provider,
+        }
+    }
+
+    pub fn from_args(provider: &str, args: &HashMap<String, String>) -> Self {
+        /*
+            Description:
+                Builds the typed configuration from a provider name and its arguments, as returned by (GET /notifier/{id}).
+                Unknown providers, or known providers missing one of their arguments, are kept in Other.
+        */
+        let arg = |name: &str| args.get(name).cloned();
+        let config = match provider {
+            "email" => arg("to").map(|to| NotifierConfig::Email { to }),
+            "gotify" => arg("url").zip(arg("token")).map(|(url, token)| NotifierConfig::Gotify { url, token }),
+            "pagerduty" => arg("routing_key").map(|routing_key| NotifierConfig::PagerDuty { routing_key }),
+            "phone" => arg("to").map(|to| NotifierConfig::Phone { to }),
+            "slack" => arg("webhook_url").map(|webhook_url| NotifierConfig::Slack { webhook_url }),
+            "telegram" => arg("chat_id").zip(arg("token")).map(|(chat_id, token)| NotifierConfig::Telegram { chat_id, token }),
+            "webhook" => arg("url").map(|url| NotifierConfig::Webhook { url }),
+            _ => None,
+        };
+        config.unwrap_or_else(|| NotifierConfig::Other { provider: provider.to_string(), args: args.clone() })
+    }
+
+    pub fn args(&self) -> HashMap<String, String> {
+        /*
+            Description:
+                Returns the provider arguments as they are sent in the form of (POST /notifier) and (PUT /notifier/{id}).
+        */
+        let pairs: Vec<(&str, &String)> = match self {
+            NotifierConfig::Email { to } | NotifierConfig::Phone { to } => vec![("to", to)],
+            NotifierConfig::Gotify { url, token } => vec![("url", url), ("token", token)],
+            NotifierConfig::PagerDuty { routing_key } => vec![("routing_key", routing_key)],
+            NotifierConfig::Slack { webhook_url } => vec![("webhook_url", webhook_url)],
+            NotifierConfig::Telegram { chat_id, token } => vec![("chat_id", chat_id), ("token", token)],
+            NotifierConfig::Webhook { url } => vec![("url", url)],
+            NotifierConfig::Other { args, .. } => return args.clone(),
+        };
+        pairs.into_iter().map(|(name, value)| (name.to_string(), value.clone())).collect()
+    }
+}
+
+/*
+    Description:
+        DNS record type used by (GET /dns/domain/{domain}). Types unknown to this enum are kept in Other(String).
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum DnsRecordType {
+    A,
+    Aaaa,
+    Cname,
+    Ns,
+    Soa,
+    Mx,
+    Txt,
+    Other(String),
+}
+
+impl DnsRecordType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            DnsRecordType::A => "A",
+            DnsRecordType::Aaaa => "AAAA",
+            DnsRecordType::Cname => "CNAME",
+            DnsRecordType::Ns => "NS",
+            DnsRecordType::Soa => "SOA",
+            DnsRecordType::Mx => "MX",
+            DnsRecordType::Txt => "TXT",
+            DnsRecordType::Other(name) => name,
+        }
+    }
+}
+
+impl From<&str> for DnsRecordType {
+    fn from(name: &str) -> Self {
+        match name.to_ascii_uppercase().as_str() {
+            "A" => DnsRecordType::A,
+            "AAAA" => DnsRecordType::Aaaa,
+            "CNAME" => DnsRecordType::Cname,
+            "NS" => DnsRecordType::Ns,
+            "SOA" => DnsRecordType::Soa,
+            "MX" => DnsRecordType::Mx,
+            "TXT" => DnsRecordType::Txt,
+            _ => DnsRecordType::Other(name.to_string()),
+        }
+    }
+}
+
+impl From<String> for DnsRecordType {
+    fn from(name: String) -> Self {
+        DnsRecordType::from(name.as_str())
+    }
+}
+
+impl From<DnsRecordType> for String {
+    fn from(record_type: DnsRecordType) -> Self {
+        record_type.as_str().to_string()
+    }
+}
+
+impl fmt::Display for DnsRecordType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/*
+    Description:
+        One page of (GET /dns/domain/{domain}): the subdomains and up to 100 DNS records, more is true when another page exists.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainInfo {
+    pub domain: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub subdomains: Vec<String>,
+    #[serde(default)]
+    pub data: Vec<DnsRecord>,
+    #[serde(default)]
+    pub more: bool,
+}
+
+/*
+    Description:
+        DNS record of a domain, subdomain is empty for the records of the domain itself.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsRecord {
+    #[serde(default)]
+    pub subdomain: String,
+    #[serde(rename = "type")]
+    pub record_type: DnsRecordType,
+    pub value: String,
+    #[serde(default)]
+    pub last_seen: Option<String>,
+    #[serde(default)]
+    pub ports: Vec<u16>,
+}
+
+/*
+    Description:
+        Host as returned by (GET /shodan/host/{ip}): the host level summary and one banner per service found on it.
+        Decode it with helpers::decode_response(shodan::get_host_ip(ip, api_key)).
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostInfo {
+    pub ip_str: String,
+    #[serde(default)]
+    pub hostnames: Vec<String>,
+    #[serde(default)]
+    pub org: Option<String>,
+    #[serde(default)]
+    pub os: Option<String>,
+    #[serde(default)]
+    pub ports: Vec<u16>,
+    #[serde(default)]
+    pub vulns: Vec<String>,
+    #[serde(default)]
+    pub data: Vec<Banner>,
+}
+
+impl HostInfo {
+    pub fn cve_ports(&self) -> BTreeMap<String, Vec<u16>> {
+        /*
+            Description:
+                Returns every CVE ID of the host (host level "vulns" and the "vulns" of every banner)
+                with the ports it was reported on (empty when only the host level list mentions it).
+        */
+        let mut cve_ports: BTreeMap<String, Vec<u16>> = self.vulns.iter().map(|cve_id| (cve_id.clone(), Vec::new())).collect();
+        for banner in &self.data {
+            for cve_id in banner.cve_ids() {
+                let ports = cve_ports.entry(cve_id).or_default();
+                if !ports.contains(&banner.port) {
+                    ports.push(banner.port);
+                }
+            }
+        }
+        cve_ports
+    }
+}
+
+/*
+    Description:
+        Service banner as found in the data of a host, a search result or the streams.
+        Only the fields every banner has are typed, everything else (http, ssl, vulns, location...) is kept as is in extra.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Banner {
+    #[serde(default)]
+    pub ip_str: Option<String>,
+    pub port: u16,
+    #[serde(default)]
+    pub transport: Option<String>,
+    #[serde(default)]
+    pub product: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Banner {
+    pub fn cve_ids(&self) -> Vec<String> {
+        /*
+            Description:
+                Returns the CVE IDs Shodan attached to this service (the keys of its "vulns" object), sorted.
+        */
+        let mut cve_ids: Vec<String> = self
+            .extra
+            .get("vulns")
+            .and_then(Value::as_object)
+            .map(|vulns| vulns.keys().cloned().collect())
+            .unwrap_or_default();
+        cve_ids.sort();
+        cve_ids
+    }
+}
+
+/*
+    Description:
+        One value of a facet, as found in the "facets" of the search and count methods (ex: {"count": 12, "value": "US"}).
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FacetValue {
+    pub count: u64,
+    pub value: Value,
+}
+
+/*
+    Description:
+        Body of (GET /shodan/host/search): the banners of the current page, the total number of results and the requested facets.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResults {
+    #[serde(default)]
+    pub matches: Vec<Banner>,
+    #[serde(default)]
+    pub total: u64,
+    #[serde(default)]
+    pub facets: HashMap<String, Vec<FacetValue>>,
+}
+
+/*
+    Description:
+        Body of (GET /account/profile). credits are the lifetime export credits of the account, created is an ISO 8601 date.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountProfile {
+    #[serde(default)]
+    pub member: bool,
+    #[serde(default)]
+    pub credits: i64,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub created: Option<String>,
+}
+
+/*
+    Description:
+        Body of (GET /api-info): the plan of the API key (ex: "oss", "dev", "edu", "basic", "plus", "corp"),
+        the credits left this month and the monthly limits in usage_limits.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiInfo {
+    pub plan: String,
+    #[serde(default)]
+    pub query_credits: i64,
+    #[serde(default)]
+    pub scan_credits: i64,
+    #[serde(default)]
+    pub monitored_ips: Option<i64>,
+    #[serde(default)]
+    pub unlocked: bool,
+    #[serde(default)]
+    pub unlocked_left: i64,
+    #[serde(default)]
+    pub https: bool,
+    #[serde(default)]
+    pub telnet: bool,
+    #[serde(default)]
+    pub usage_limits: UsageLimits,
+}
+
+/*
+    Description:
+        Monthly limits of the API plan, -1 meaning unlimited.
+*/
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageLimits {
+    #[serde(default)]
+    pub scan_credits: i64,
+    #[serde(default)]
+    pub query_credits: i64,
+    #[serde(default)]
+    pub monitored_ips: i64,
+}
+
+/*
+    Description:
+        Property the directory of saved search queries (GET /shodan/query) can be sorted on.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortBy {
+    Votes,
+    Timestamp,
+}
+
+impl SortBy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortBy::Votes => "votes",
+            SortBy::Timestamp => "timestamp",
+        }
+    }
+}
+
+impl fmt::Display for SortBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl Order {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Order::Asc => "asc",
+            Order::Desc => "desc",
+        }
+    }
+}
+
+impl fmt::Display for Order {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/*
+    Description:
+        Search query saved by a Shodan user, as listed by (GET /shodan/query) and (GET /shodan/query/search).
+        timestamp is the date it was saved (ex: "2014-01-15T05:49:56.283713").
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub query: String,
+    #[serde(default)]
+    pub votes: i64,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub timestamp: String,
+}
+
+/*
+    Description:
+        One page (10 queries) of the directory of saved search queries, total being the number of queries of the whole directory/ search.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedQueryPage {
+    #[serde(default)]
+    pub total: u64,
+    #[serde(default)]
+    pub matches: Vec<SavedQuery>,
+}
+
+/*
+    Description:
+        Tag of the saved search queries and the number of queries using it, as listed by (GET /shodan/query/tags).
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryTag {
+    pub value: String,
+    pub count: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    fn args(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn notifier_config_from_known_providers() {
+        assert_eq!(
+            NotifierConfig::from_args("email", &args(&[("to", "jmath@shodan.io")])),
+            NotifierConfig::Email { to: "jmath@shodan.io".to_string() }
+        );
+        assert_eq!(
+            NotifierConfig::from_args("telegram", &args(&[("chat_id", "42"), ("token", "t")])),
+            NotifierConfig::Telegram { chat_id: "42".to_string(), token: "t".to_string() }
+        );
+        assert_eq!(
+            NotifierConfig::from_args("webhook", &args(&[("url", "https://example.com/hook"), ("extra", "kept out")])),
+            NotifierConfig::Webhook { url: "https://example.com/hook".to_string() }
+        );
+    }
+
+    #[test]
+    fn notifier_config_keeps_the_rest_in_other() {
+        let missing = args(&[("url", "https://gotify.example.com")]);
+        assert_eq!(
+            NotifierConfig::from_args("gotify", &missing),
+            NotifierConfig::Other { provider: "gotify".to_string(), args: missing.clone() }
+        );
+        let unknown = args(&[("room", "ops")]);
+        let config = NotifierConfig::from_args("matrix", &unknown);
+        assert_eq!(config.provider(), "matrix");
+        assert_eq!(config.args(), unknown);
+    }
+
+    #[test]
+    fn notifier_config_args_round_trip() {
+        let sent = args(&[("chat_id", "42"), ("token", "t")]);
+        assert_eq!(NotifierConfig::from_args("telegram", &sent).args(), sent);
+    }
+
+    #[test]
+    fn notifier_with_non_string_args_decodes() {
+        let notifier: Notifier = serde_json::from_value(json!({
+            "id": "abc",
+            "provider": "telegram",
+            "description": "ops",
+            "args": {"chat_id": 42, "token": "t", "silent": null}
+        }))
+        .unwrap();
+        assert_eq!(
+            notifier.config(),
+            NotifierConfig::Telegram { chat_id: "42".to_string(), token: "t".to_string() }
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use crate::credentials::ApiKey;
+use crate::error::ShodanError;
+use crate::models::NotifierConfig;
+use crate::shodan;
+
+pub fn get_provider_schemas(api_key: &ApiKey) -> Result<HashMap<String, Vec<String>>, ShodanError> {
+    /*
+        Description:
+            Returns the required arguments of every notification provider, by provider name.
+            Thin wrapper over shodan::get_notifier_provider() (GET /notifier/provider), which returns the typed providers.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+        Returns:
+            Result<HashMap<String, Vec<String>>, ShodanError>:
+                Ok(HashMap<String, Vec<String>>): Returns the names of the required arguments, by provider name.
+                Err(ShodanError): Returns an error if the request fails or returns an unexpected body.
+    */
+    Ok(shodan::get_notifier_provider(api_key)?
+        .into_iter()
+        .map(|provider| (provider.name, provider.required))
+        .collect())
+}
+
+pub fn validate_notifier_config(api_key: &ApiKey, config: &NotifierConfig) -> Result<(), ShodanError> {
+    /*
+        Description:
+            Checks a notifier configuration against the schema Shodan reports at runtime (GET /notifier/provider):
+            the provider must exist and every required argument must be given a non-empty value.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            config (&NotifierConfig): The configuration to check.
+        Returns:
+            Result<(), ShodanError>:
+                Ok(()): The configuration can be sent to create_notifier()/ edit_notifier().
+                Err(ShodanError): Returns ShodanError::Invalid naming the unknown provider or the missing arguments, or the error of the request.
+    */
+    let providers = shodan::get_notifier_provider(api_key)?;
+    let provider = providers
+        .iter()
+        .find(|provider| provider.name == config.provider())
+        .ok_or_else(|| ShodanError::Invalid(format!("unknown notifier provider \"{}\"", config.provider())))?;
+
+    let args = config.args();
+    let missing: Vec<&str> = provider
+        .required
+        .iter()
+        .filter(|name| args.get(name.as_str()).is_none_or(|value| value.trim().is_empty()))
+        .map(String::as_str)
+        .collect();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(ShodanError::Invalid(format!(
+            "notifier provider \"{}\" is missing required argument(s): {}",
+            config.provider(),
+            missing.join(", ")
+        )))
+    }
+}
+
+pub fn get_notifier_config(api_key: &ApiKey, notifier_id: &str) -> Result<NotifierConfig, ShodanError> {
+    /*
+        Description:
+            Returns the typed configuration of an existing notifier.
+            Thin wrapper over shodan::get_notifier_info() (GET /notifier/{id}) and Notifier::config().
+            Handy to change one argument and send the full map back with edit_notifier().
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            notifier_id (&str): Notifier ID returned by (POST /notifier)
+        Returns:
+            Result<NotifierConfig, ShodanError>:
+                Ok(NotifierConfig): Returns the provider and arguments of the notifier.
+                Err(ShodanError): Returns an error if the request fails or returns an unexpected body.
+    */
+    Ok(shodan::get_notifier_info(notifier_id, api_key)?.config())
+}
@@ -0,0 +1,335 @@
+use std::fmt::Write;
+use std::str::FromStr;
+use serde_json::{Map, Value};
+use crate::error::ShodanError;
+use crate::helpers;
+
+// Longest cell printed by the table format, longer values are cut
+const TABLE_MAX_WIDTH: usize = 50;
+
+/*
+    Description:
+        How results are printed:
+            Table: aligned columns, for reading in a terminal.
+            Json: one pretty-printed JSON document.
+            Jsonl: one compact JSON object per result line, for jq and data lakes.
+            Csv: RFC 4180 CSV with a header line, for spreadsheets.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Jsonl,
+    Csv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = ShodanError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" => Ok(OutputFormat::Jsonl),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(ShodanError::Invalid(format!("unknown output format \"{}\", expected table, json, jsonl or csv", name))),
+        }
+    }
+}
+
+pub fn flatten(value: &Value) -> Map<String, Value> {
+    /*
+        Description:
+            Flattens nested objects into dotted names (ex: {"location": {"country_code": "US"}} -> {"location.country_code": "US"}).
+            Arrays are kept as they are. A value that is not an object is returned under the name "value".
+    */
+    let mut flat = Map::new();
+    match value {
+        Value::Object(object) => flatten_into(&mut flat, "", object),
+        value => {
+            flat.insert("value".to_string(), value.clone());
+        }
+    }
+    flat
+}
+
+fn flatten_into(flat: &mut Map<String, Value>, prefix: &str, object: &Map<String, Value>) {
+    for (name, value) in object {
+        let name = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+        match value {
+            Value::Object(nested) if !nested.is_empty() => flatten_into(flat, &name, nested),
+            value => {
+                flat.insert(name, value.clone());
+            }
+        }
+    }
+}
+
+pub fn result_rows(value: &Value) -> Vec<&Value> {
+    /*
+        Description:
+            Splits a result into the rows to print: the items of an array, the "matches" of a search/ listing page
+            (ex: GET /shodan/host/search), or the value itself.
+    */
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(object) => match object.get("matches") {
+            Some(Value::Array(matches)) => matches.iter().collect(),
+            _ => vec![value],
+        },
+        value => vec![value],
+    }
+}
+
+pub fn render(value: &Value, format: OutputFormat, fields: &[String]) -> Result<String, ShodanError> {
+    /*
+        Description:
+            Formats a result (see result_rows()) for printing.
+        Parameters:
+            value (&Value): The result, as JSON.
+            format (OutputFormat): The output format.
+            fields (&[String]): Dotted names of the columns to keep (ex: ["ip_str", "port", "location.country_code"]),
+            empty for every column. With Json and Jsonl, an empty list prints the results unflattened.
+        Returns:
+            Result<String, ShodanError>:
+                Ok(String): Returns the text to print, ending with a line break.
+                Err(ShodanError): Returns an error if a row can't be encoded as JSON.
+    */
+    let rows = result_rows(value);
+    let mut out = String::new();
+
+    match format {
+        OutputFormat::Json if fields.is_empty() => {
+            out.push_str(&serde_json::to_string_pretty(value)?);
+            out.push('\n');
+        }
+        OutputFormat::Json => {
+            let selected: Vec<Value> = rows.iter().map(|row| Value::Object(select(row, fields))).collect();
+            out.push_str(&serde_json::to_string_pretty(&selected)?);
+            out.push('\n');
+        }
+        OutputFormat::Jsonl => {
+            for row in rows {
+                out.push_str(&jsonl_line(row, fields)?);
+            }
+        }
+        OutputFormat::Csv => {
+            let flat: Vec<Map<String, Value>> = rows.into_iter().map(flatten).collect();
+            let columns = columns(&flat, fields);
+            out.push_str(&csv_line(&columns));
+            for row in &flat {
+                let cells: Vec<String> = columns.iter().map(|column| cell(row.get(column))).collect();
+                out.push_str(&csv_line(&cells));
+            }
+        }
+        OutputFormat::Table => {
+            let flat: Vec<Map<String, Value>> = rows.into_iter().map(flatten).collect();
+            let columns = columns(&flat, fields);
+            let cells: Vec<Vec<String>> = flat
+                .iter()
+                .map(|row| columns.iter().map(|column| table_cell(row.get(column))).collect())
+                .collect();
+            let widths: Vec<usize> = columns
+                .iter()
+                .enumerate()
+                .map(|(index, column)| cells.iter().map(|row| row[index].chars().count()).fold(column.chars().count(), usize::max))
+                .collect();
+
+            let separator: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+            table_line(&mut out, &columns, &widths);
+            table_line(&mut out, &separator, &widths);
+            for row in &cells {
+                table_line(&mut out, row, &widths);
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/*
+    Description:
+        Formats the banners of a stream one by one, as they come. The columns of Csv and Table are the fields given,
+        or the ones of the first banner when none is. Table columns are separated by tabs, their width being unknown in advance.
+*/
+pub struct StreamWriter {
+    format: OutputFormat,
+    fields: Vec<String>,
+    header_written: bool,
+}
+
+impl StreamWriter {
+    pub fn new(format: OutputFormat, fields: Vec<String>) -> Self {
+        StreamWriter { format, fields, header_written: false }
+    }
+
+    pub fn line(&mut self, banner: &Value) -> Result<String, ShodanError> {
+        /*
+            Description:
+                Returns the text to print for one banner (the header first, for the first banner of a Csv or Table stream).
+        */
+        if matches!(self.format, OutputFormat::Json | OutputFormat::Jsonl) {
+            return jsonl_line(banner, &self.fields);
+        }
+
+        let flat = flatten(banner);
+        let mut out = String::new();
+        if !self.header_written {
+            if self.fields.is_empty() {
+                self.fields = flat.keys().cloned().collect();
+            }
+            self.header_written = true;
+            out.push_str(&self.join_line(&self.fields));
+        }
+        let cells: Vec<String> = match self.format {
+            OutputFormat::Table => self.fields.iter().map(|field| table_cell(flat.get(field))).collect(),
+            _ => self.fields.iter().map(|field| cell(flat.get(field))).collect(),
+        };
+        out.push_str(&self.join_line(&cells));
+        Ok(out)
+    }
+
+    fn join_line(&self, cells: &[String]) -> String {
+        match self.format {
+            OutputFormat::Table => format!("{}\n", cells.join("\t")),
+            _ => csv_line(cells),
+        }
+    }
+}
+
+fn select(row: &Value, fields: &[String]) -> Map<String, Value> {
+    let flat = flatten(row);
+    fields
+        .iter()
+        .map(|field| (field.clone(), flat.get(field).cloned().unwrap_or(Value::Null)))
+        .collect()
+}
+
+fn columns(rows: &[Map<String, Value>], fields: &[String]) -> Vec<String> {
+    // The fields asked for, or every name found in the rows, in order of first appearance
+    if !fields.is_empty() {
+        return fields.to_vec();
+    }
+    let mut columns: Vec<String> = Vec::new();
+    for row in rows {
+        for name in row.keys() {
+            if !columns.contains(name) {
+                columns.push(name.clone());
+            }
+        }
+    }
+    columns
+}
+
+fn jsonl_line(row: &Value, fields: &[String]) -> Result<String, ShodanError> {
+    let line = if fields.is_empty() {
+        serde_json::to_string(row)?
+    } else {
+        serde_json::to_string(&select(row, fields))?
+    };
+    Ok(format!("{}\n", line))
+}
+
+fn cell(value: Option<&Value>) -> String {
+    // Text of a cell: strings as is, arrays of plain values joined with ";", anything else as compact JSON
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(items)) if items.iter().all(|item| !item.is_array() && !item.is_object()) => items
+            .iter()
+            .map(|item| cell(Some(item)))
+            .collect::<Vec<_>>()
+            .join(";"),
+        Some(value) => value.to_string(),
+    }
+}
+
+fn table_cell(value: Option<&Value>) -> String {
+    let text = cell(value).replace(['\n', '\r', '\t'], " ");
+    if text.chars().count() > TABLE_MAX_WIDTH {
+        format!("{}...", text.chars().take(TABLE_MAX_WIDTH - 3).collect::<String>())
+    } else {
+        text
+    }
+}
+
+fn csv_line(cells: &[String]) -> String {
+    let mut line = cells.iter().map(|cell| helpers::csv_field(cell)).collect::<Vec<_>>().join(",");
+    line.push('\n');
+    line
+}
+
+fn table_line(out: &mut String, cells: &[String], widths: &[usize]) {
+    let line = cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| format!("{:width$}", cell, width = width))
+        .collect::<Vec<_>>()
+        .join("  ");
+    let _ = writeln!(out, "{}", line.trim_end());
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    fn banners() -> Value {
+        json!({
+            "total": 2,
+            "matches": [
+                {"ip_str": "1.2.3.4", "port": 443, "location": {"country_code": "US", "city": "Austin, TX"}, "hostnames": ["a.example.com", "b.example.com"]},
+                {"ip_str": "5.6.7.8", "port": 80, "location": {"country_code": "DE"}, "data": "HTTP/1.1 200 OK\r\nServer: \"x\""}
+            ]
+        })
+    }
+
+    fn fields(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn flatten_uses_dotted_names() {
+        let flat = flatten(&json!({"ip_str": "1.2.3.4", "location": {"country_code": "US", "geo": {"lat": 1.5}}, "opts": {}, "ports": [80, 443]}));
+        assert_eq!(flat.get("location.country_code"), Some(&json!("US")));
+        assert_eq!(flat.get("location.geo.lat"), Some(&json!(1.5)));
+        assert_eq!(flat.get("opts"), Some(&json!({})));
+        assert_eq!(flat.get("ports"), Some(&json!([80, 443])));
+        assert!(flat.get("location").is_none());
+
+        assert_eq!(flatten(&json!(42)).get("value"), Some(&json!(42)));
+    }
+
+    #[test]
+    fn csv_keeps_the_fields_asked_for() {
+        let csv = render(&banners(), OutputFormat::Csv, &fields(&["ip_str", "location.city", "hostnames", "missing"])).unwrap();
+        assert_eq!(csv, "ip_str,location.city,hostnames,missing\n1.2.3.4,\"Austin, TX\",a.example.com;b.example.com,\n5.6.7.8,,,\n");
+    }
+
+    #[test]
+    fn csv_without_fields_has_every_column() {
+        let csv = render(&banners(), OutputFormat::Csv, &[]).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("ip_str,port,location.country_code,location.city,hostnames,data"));
+        assert_eq!(csv.matches("\"HTTP/1.1 200 OK\r\nServer: \"\"x\"\"\"").count(), 1);
+    }
+
+    #[test]
+    fn table_aligns_the_fields_asked_for() {
+        let table = render(&banners(), OutputFormat::Table, &fields(&["ip_str", "port", "data"])).unwrap();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "ip_str   port  data");
+        assert_eq!(lines[1], format!("-------  ----  {}", "-".repeat(28)));
+        assert_eq!(lines[2], "1.2.3.4  443");
+        assert_eq!(lines[3], "5.6.7.8  80    HTTP/1.1 200 OK  Server: \"x\"");
+    }
+
+    #[test]
+    fn jsonl_selects_fields_per_row() {
+        let jsonl = render(&banners(), OutputFormat::Jsonl, &fields(&["ip_str", "location.country_code"])).unwrap();
+        assert_eq!(
+            jsonl,
+            "{\"ip_str\":\"1.2.3.4\",\"location.country_code\":\"US\"}\n{\"ip_str\":\"5.6.7.8\",\"location.country_code\":\"DE\"}\n"
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use std::fmt;
+use crate::error::ShodanError;
+use crate::models::ApiInfo;
+
+/*
+    Description:
+        Shodan API plans, ordered from the most to the least limited, so a plan includes everything the previous ones can do.
+        Built from the "plan" field of (GET /api-info) with PlanTier::from_plan().
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PlanTier {
+    Free,
+    Membership,
+    Freelancer,
+    SmallBusiness,
+    Corporate,
+    Enterprise,
+}
+
+impl PlanTier {
+    pub fn from_plan(plan: &str) -> Option<PlanTier> {
+        /*
+            Description:
+                Returns the tier of a plan code (ex: "oss", "dev", "plus"), None for a plan code we do not know.
+        */
+        match plan {
+            "oss" => Some(PlanTier::Free),
+            "dev" | "edu" | "stu" => Some(PlanTier::Membership),
+            "basic" => Some(PlanTier::Freelancer),
+            "plus" => Some(PlanTier::SmallBusiness),
+            "corp" => Some(PlanTier::Corporate),
+            "enterprise" => Some(PlanTier::Enterprise),
+            plan if plan.starts_with("stream") => Some(PlanTier::Enterprise),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlanTier::Free => "Free",
+            PlanTier::Membership => "Membership",
+            PlanTier::Freelancer => "Freelancer",
+            PlanTier::SmallBusiness => "Small Business",
+            PlanTier::Corporate => "Corporate",
+            PlanTier::Enterprise => "Enterprise",
+        }
+    }
+}
+
+impl fmt::Display for PlanTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/*
+    Description:
+        Features of the API that are not available on every plan, see Capability::required_plan().
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    SearchFilters,
+    SearchPaging,
+    VulnFilter,
+    TagFilter,
+    OnDemandScan,
+    NetworkAlerts,
+    Streaming,
+}
+
+impl Capability {
+    pub fn required_plan(&self) -> PlanTier {
+        /*
+            Description:
+                Lowest plan giving access to the capability.
+        */
+        match self {
+            Capability::SearchFilters => PlanTier::Membership,
+            Capability::SearchPaging => PlanTier::Membership,
+            Capability::VulnFilter => PlanTier::SmallBusiness,
+            Capability::TagFilter => PlanTier::Corporate,
+            Capability::OnDemandScan => PlanTier::Membership,
+            Capability::NetworkAlerts => PlanTier::Membership,
+            Capability::Streaming => PlanTier::Enterprise,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Capability::SearchFilters => "search filters",
+            Capability::SearchPaging => "search result pages after the first",
+            Capability::VulnFilter => "the vuln search filter",
+            Capability::TagFilter => "the tag search filter",
+            Capability::OnDemandScan => "on-demand scanning",
+            Capability::NetworkAlerts => "network alerts",
+            Capability::Streaming => "the banner stream",
+        }
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+pub fn require(info: &ApiInfo, capability: Capability) -> Result<(), ShodanError> {
+    /*
+        Description:
+            Capability check
+            Refuses locally a call the plan of the API key cannot make, instead of getting a 403 from Shodan.
+            Plans we do not know are let through, Shodan will decide.
+        Parameters:
+            info (&ApiInfo): Plan information decoded from (GET /api-info).
+            capability (Capability): What the next call needs.
+        Returns:
+            Result<(), ShodanError>:
+                Ok(()): The plan includes the capability (or is unknown).
+                Err(ShodanError::PlanRequired): The plan is too limited, the error names the plan needed.
+    */
+    let required = capability.required_plan();
+    match PlanTier::from_plan(&info.plan) {
+        Some(tier) if tier < required => Err(ShodanError::PlanRequired { capability, required, plan: info.plan.clone() }),
+        _ => Ok(()),
+    }
+}
+
+pub fn require_for_query(info: &ApiInfo, query: &str) -> Result<(), ShodanError> {
+    /*
+        Description:
+            Capability check of a search query
+            Checks every "filter:value" of a query (ex: "apache country:DE vuln:CVE-2021-44228") against the plan of the
+            API key before sending it to (GET /shodan/host/search) or (GET /shodan/host/count).
+        Parameters:
+            info (&ApiInfo): Plan information decoded from (GET /api-info).
+            query (&str): Shodan search query
+        Returns:
+            Result<(), ShodanError>:
+                Ok(()): The plan can run the query (or is unknown).
+                Err(ShodanError::PlanRequired): A filter of the query needs a higher plan, the error names it.
+    */
+    for filter in query_filters(query) {
+        let capability = match filter {
+            "vuln" => Capability::VulnFilter,
+            "tag" => Capability::TagFilter,
+            _ => Capability::SearchFilters,
+        };
+        require(info, capability)?;
+    }
+    Ok(())
+}
+
+pub fn require_for_search(info: &ApiInfo, query: &str, page: u32) -> Result<(), ShodanError> {
+    /*
+        Description:
+            Same as require_for_query(), also checking that the plan can get pages after the first of (GET /shodan/host/search).
+    */
+    require_for_query(info, query)?;
+    if page > 1 {
+        require(info, Capability::SearchPaging)?;
+    }
+    Ok(())
+}
+
+pub fn search_query_credits(query: &str, page: u32) -> i64 {
+    /*
+        Description:
+            Query credits used by a page of (GET /shodan/host/search): 1 when the query has a filter or the page is after
+            the first, 0 otherwise.
+    */
+    if page > 1 || !query_filters(query).is_empty() {
+        1
+    } else {
+        0
+    }
+}
+
+fn query_filters(query: &str) -> Vec<&str> {
+    // Names of the "filter:value" tokens of a query, skipping the quoted parts ("title:foo" is a search term in quotes)
+    let mut filters = Vec::new();
+    let mut in_quotes = false;
+    for token in query.split(' ') {
+        let starts_quoted = in_quotes;
+        in_quotes ^= token.matches('"').count() % 2 == 1;
+        if starts_quoted || token.starts_with('"') {
+            continue;
+        }
+        if let Some((name, _)) = token.trim_start_matches('-').split_once(':') {
+            if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_') {
+                filters.push(name);
+            }
+        }
+    }
+    filters
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    fn info(plan: &str) -> ApiInfo {
+        serde_json::from_value(json!({ "plan": plan })).unwrap()
+    }
+
+    #[test]
+    fn query_filters_finds_filter_names() {
+        assert_eq!(query_filters("apache"), Vec::<&str>::new());
+        assert_eq!(query_filters("apache country:DE port:443"), vec!["country", "port"]);
+        assert_eq!(query_filters("-port:22 ssl.cert.expired:true"), vec!["port", "ssl.cert.expired"]);
+        assert_eq!(query_filters("org:\"Acme Corp\" vuln:CVE-2021-44228"), vec!["org", "vuln"]);
+    }
+
+    #[test]
+    fn query_filters_skips_quoted_terms() {
+        assert_eq!(query_filters("\"title:foo\" nginx"), Vec::<&str>::new());
+        assert_eq!(query_filters("\"hello tag:ics world\" tag:ics"), vec!["tag"]);
+        assert_eq!(query_filters(":80 -:x"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn require_for_search_checks_filters_and_pages() {
+        assert!(require_for_search(&info("oss"), "apache", 1).is_ok());
+        assert!(matches!(
+            require_for_search(&info("oss"), "apache country:DE", 1),
+            Err(ShodanError::PlanRequired { capability: Capability::SearchFilters, required: PlanTier::Membership, .. })
+        ));
+        assert!(matches!(
+            require_for_search(&info("oss"), "apache", 2),
+            Err(ShodanError::PlanRequired { capability: Capability::SearchPaging, .. })
+        ));
+        assert!(require_for_search(&info("dev"), "apache country:DE", 2).is_ok());
+        assert!(matches!(
+            require_for_search(&info("dev"), "vuln:CVE-2021-44228", 1),
+            Err(ShodanError::PlanRequired { capability: Capability::VulnFilter, required: PlanTier::SmallBusiness, .. })
+        ));
+        assert!(require_for_search(&info("corp"), "tag:ics vuln:CVE-2021-44228", 3).is_ok());
+        assert!(require_for_search(&info("some-new-plan"), "tag:ics", 2).is_ok());
+    }
+
+    #[test]
+    fn search_query_credits_counts_filters_and_pages() {
+        assert_eq!(search_query_credits("apache", 1), 0);
+        assert_eq!(search_query_credits("apache", 2), 1);
+        assert_eq!(search_query_credits("apache country:DE", 1), 1);
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::VecDeque;
+use crate::credentials::ApiKey;
+use crate::error::ShodanError;
+use crate::helpers::RateLimiter;
+use crate::models::{Order, SavedQuery, SavedQueryPage, SortBy};
+use crate::shodan;
+
+// What SavedQueries pages through
+enum Listing<'a> {
+    Directory { sort: Option<SortBy>, order: Option<Order> },
+    Search { query: &'a str },
+}
+
+/*
+    Description:
+        Iterator over the whole directory of saved search queries, paging through (GET /shodan/query) or
+        (GET /shodan/query/search) 10 queries at a time until the reported total is reached or a page comes back empty.
+        A failed page is yielded as an error and ends the iteration.
+*/
+pub struct SavedQueries<'a> {
+    api_key: &'a ApiKey,
+    listing: Listing<'a>,
+    next_page: Option<u32>,
+    buffer: VecDeque<SavedQuery>,
+    seen: u64,
+    total: Option<u64>,
+    limiter: RateLimiter,
+}
+
+impl<'a> SavedQueries<'a> {
+    pub fn directory(api_key: &'a ApiKey, sort: Option<SortBy>, order: Option<Order>) -> Self {
+        /*
+            Description:
+                Pages through every saved search query (GET /shodan/query), no request is sent before the first call to next().
+            Parameters:
+                api_key (&ApiKey): Shodan API Key
+                sort (Option<SortBy>): Sort the list based on a property, None for the Shodan default
+                order (Option<Order>): Whether to sort the list in ascending or descending order, None for the Shodan default
+        */
+        SavedQueries::new(api_key, Listing::Directory { sort, order })
+    }
+
+    pub fn search(api_key: &'a ApiKey, query: &'a str) -> Self {
+        /*
+            Description:
+                Pages through every saved search query matching query (GET /shodan/query/search), no request is sent before
+                the first call to next().
+            Parameters:
+                api_key (&ApiKey): Shodan API Key
+                query (&str): What to search for in the directory of saved search queries; example "webcam"
+        */
+        SavedQueries::new(api_key, Listing::Search { query })
+    }
+
+    fn new(api_key: &'a ApiKey, listing: Listing<'a>) -> Self {
+        SavedQueries {
+            api_key,
+            listing,
+            next_page: Some(1),
+            buffer: VecDeque::new(),
+            seen: 0,
+            total: None,
+            limiter: RateLimiter::default(),
+        }
+    }
+
+    pub fn total(&self) -> Option<u64> {
+        /*
+            Description:
+                Number of saved queries reported by Shodan, None before the first page is fetched.
+        */
+        self.total
+    }
+
+    fn fetch_next_page(&mut self) -> Result<(), ShodanError> {
+        let page = match self.next_page {
+            Some(page) => page,
+            None => return Ok(()),
+        };
+
+        self.limiter.wait();
+        self.next_page = None;
+        let result: SavedQueryPage = match &self.listing {
+            Listing::Directory { sort, order } => shodan::get_query(page, *sort, *order, self.api_key)?,
+            Listing::Search { query } => shodan::get_query_search(query, page, self.api_key)?,
+        };
+
+        self.total = Some(result.total);
+        self.seen += result.matches.len() as u64;
+        if !result.matches.is_empty() && self.seen < result.total {
+            self.next_page = Some(page + 1);
+        }
+        self.buffer.extend(result.matches);
+        Ok(())
+    }
+}
+
+impl Iterator for SavedQueries<'_> {
+    type Item = Result<SavedQuery, ShodanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.buffer.is_empty() && self.next_page.is_some() {
+            if let Err(err) = self.fetch_next_page() {
+                return Some(Err(err));
+            }
+        }
+        self.buffer.pop_front().map(Ok)
+    }
+}
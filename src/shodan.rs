@@ -1,28 +1,54 @@
-use serde_json::json;
+use std::collections::HashMap;
+use serde::Deserialize;
+use serde_json::{json, Value};
 use reqwest::blocking::Client;
 use reqwest::blocking::Response;
 use reqwest::Error as ReqwestError;
+use crate::credentials::ApiKey;
+use crate::error::ShodanError;
+use crate::helpers;
+use crate::plans::{self, Capability};
+use crate::models::{
+    AccountProfile, Alert, AlertTrigger, ApiInfo, DnsRecordType, DomainInfo, Notifier, NotifierConfig, NotifierProvider, Order,
+    QueryTag, SavedQueryPage, SortBy,
+};
 
-pub fn get_host_ip(ip: &str, api_key: &str) -> Result<Response, ReqwestError> {
+// Bodies only used to reach the interesting part of a response
+#[derive(Deserialize)]
+struct NotifierListing {
+    matches: Vec<Notifier>,
+}
+
+#[derive(Deserialize)]
+struct CreatedNotifier {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct QueryTagListing {
+    matches: Vec<QueryTag>,
+}
+
+pub fn get_host_ip(ip: &str, api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             Host Information - Returns all services that have been found on the given host IP. 
             (GET/shodan/host/{ip})   
         Parameters:
             ip (&str): Host IP address
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/host/{}?key={}", ip, api_key);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/host/{}?key={}", ip, api_key.expose());
+    let response = helpers::send("get_host_ip", client.get(&url))?;
     Ok(response)
 }
 
-pub fn get_host_count(api_key: &str, query: &str, facets: &str) -> Result<Response, ReqwestError> {
+pub fn get_host_count(api_key: &ApiKey, query: &str, facets: &str) -> Result<Response, ReqwestError> {
     /*
         Description:
             Search Shodan without Results
@@ -31,7 +57,7 @@ pub fn get_host_count(api_key: &str, query: &str, facets: &str) -> Result<Respon
             As a result this method does not consume query credits.
             (GET /shodan/host/count)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
             query (&str): Shodan search query. The provided string is used to search the database of banners in Shodan, 
             with the additional option to provide filters inside the search query using a "filter:value" format. 
             For example, the following search query would find Apache Web servers located in Germany: "apache country:DE". (https://beta.shodan.io/search/filters)
@@ -44,79 +70,83 @@ pub fn get_host_count(api_key: &str, query: &str, facets: &str) -> Result<Respon
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/host/count?key={}&query={}&facets={}", api_key, query, facets);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/host/count?key={}", api_key.expose());
+    let response = helpers::send("get_host_count", client.get(&url).query(&[("query", query), ("facets", facets)]))?;
     Ok(response)
 }
 
-pub fn get_host_search(api_key: &str, query: &str, facets: &str) -> Result<Response, ReqwestError> {
+pub fn get_host_search(api_key: &ApiKey, query: &str, facets: &str, page: u32) -> Result<Response, ReqwestError> {
     /*
         Description:
             Search Shodan
             Search Shodan using the same query syntax as the website and use facets to get summary information for different properties.
             (GET /shodan/host/search)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
             query (&str): Shodan search query. The provided string is used to search the database of banners in Shodan, 
             with the additional option to provide filters inside the search query using a "filter:value" format. 
             For example, the following search query would find Apache Web servers located in Germany: "apache country:DE". (https://beta.shodan.io/search/filters)
             facets (&str):  A comma-separated list of properties to get summary information on. Property names can also be in the format of "property:count", 
             where "count" is the number of facets that will be returned for a property (i.e. "country:100" to get the top 100 countries for a search query). 
             Visit the Shodan website's Facet Analysis page for an up-to-date list of available facets: https://beta.shodan.io/search/facet
+            page (u32): Page of 100 results, starting at 1. Pages after the first cost 1 query credit each (see plans::require_for_search()).
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/host/search?key={}&query={}&facets={}", api_key, query, facets);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/host/search?key={}", api_key.expose());
+    let credits = plans::search_query_credits(query, page);
+    let page = page.to_string();
+    let request = client.get(&url).query(&[("query", query), ("facets", facets), ("page", &page)]);
+    let response = helpers::send_with_credits("get_host_search", request, credits)?;
     Ok(response)
 }
 
-pub fn get_facets_list(api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_facets_list(api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             List all search facets - This method returns a list of facets that can be used to get a breakdown of the top values for a property.
             (GET /shodan/host/search/facets)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/host/search/facets?key={}", api_key);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/host/search/facets?key={}", api_key.expose());
+    let response = helpers::send("get_facets_list", client.get(&url))?;
     Ok(response)
 }
 
-pub fn get_filters_list(api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_filters_list(api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             List all filters that can be used when searching - This method returns a list of search filters that can be used in the search query.
             (GET /shodan/host/search/filters)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/host/search/filters?key={}", api_key);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/host/search/filters?key={}", api_key.expose());
+    let response = helpers::send("get_filters_list", client.get(&url))?;
     Ok(response)
 }
 
-pub fn get_host_search_tokens(api_key: &str, query: &str) -> Result<Response, ReqwestError> {
+pub fn get_host_search_tokens(api_key: &ApiKey, query: &str) -> Result<Response, ReqwestError> {
     /*
         Description:
             Break the search query into tokens - This method lets you determine which filters are being used by the query string and what parameters were provided to the filters.
             (GET /shodan/host/search/tokens)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
             query (&str): Shodan search query. The provided string is used to search the database of banners in Shodan, 
             with the additional option to provide filters inside the search query using a "filter:value" format. 
             For example, the following search query would find Apache Web servers located in Germany: "apache country:DE". (https://beta.shodan.io/search/filters)
@@ -126,56 +156,56 @@ pub fn get_host_search_tokens(api_key: &str, query: &str) -> Result<Response, Re
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/host/search/tokens?key={}&query={}", api_key, query);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/host/search/tokens?key={}", api_key.expose());
+    let response = helpers::send("get_host_search_tokens", client.get(&url).query(&[("query", query)]))?;
     Ok(response)
 }
 
-pub fn get_ports(api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_ports(api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             List all ports that Shodan is crawling on the Internet. - This method returns a list of port numbers that the crawlers are looking for.
             (GET /shodan/ports)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/ports?key={}", api_key);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/ports?key={}", api_key.expose());
+    let response = helpers::send("get_ports", client.get(&url))?;
     Ok(response)
 }
 
-pub fn get_protocols(api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_protocols(api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             List all protocols that can be used when performing on-demand Internet scans via Shodan.
             This method returns an object containing all the protocols that can be used when launching an Internet scan.
             (GET /shodan/protocols)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/protocols?key={}", api_key);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/protocols?key={}", api_key.expose());
+    let response = helpers::send("get_protocols", client.get(&url))?;
     Ok(response)
 }
 
-pub fn create_scan(api_key: &str, ips_or_ips_and_services: &str) -> Result<Response, ReqwestError> {
+pub fn create_scan(api_key: &ApiKey, ips_or_ips_and_services: &str) -> Result<Response, ReqwestError> {
     /*
         Description:
             Request Shodan to crawl an IP/ netblock - Use this method to request Shodan to crawl a network.
             WARNING ! -> This method uses API scan credits: 1 IP consumes 1 scan credit. You must have a paid API plan (either one-time payment or subscription) in order to use this method.
             (POST /shodan/scan)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
             ips_or_ips_and_services (&str): A string containing either single, multiple IP addresses or a JSON-formatted string representing a list of IPs with optional specified services.
         Returns:
             Result<Response, ReqwestError>: 
@@ -183,31 +213,31 @@ pub fn create_scan(api_key: &str, ips_or_ips_and_services: &str) -> Result<Respo
                 Err(ReqwestError): Returns an error if the POST request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/scan?key={}", api_key);
-    let response = client.post(url).form(&[("ips", ips_or_ips_and_services)]).send()?;
+    let url = format!("https://api.shodan.io/shodan/scan?key={}", api_key.expose());
+    let response = helpers::send("create_scan", client.post(url).form(&[("ips", ips_or_ips_and_services)]))?;
     Ok(response)
 }
 
-pub fn get_scans(api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_scans(api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             Get list of all the created scans
             Returns a listing of all the on-demand scans that are currently active on the account.
             (GET /shodan/scans)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/scans?key={}", api_key);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/scans?key={}", api_key.expose());
+    let response = helpers::send("get_scans", client.get(&url))?;
     Ok(response)
 }
 
-pub fn get_scan_id(scan_id: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_scan_id(scan_id: &str, api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             Get the status of a scan request
@@ -219,36 +249,36 @@ pub fn get_scan_id(scan_id: &str, api_key: &str) -> Result<Response, ReqwestErro
             (GET /shodan/scan/{id})
         Parameters:
             id (&str): The unique scan ID that was returned by /shodan/scan.
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/scan/{}?key={}", scan_id, api_key);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/scan/{}?key={}", scan_id, api_key.expose());
+    let response = helpers::send("get_scan_id", client.get(&url))?;
     Ok(response)
 }
 
-pub fn create_alert(api_key: &str, name: &str, ips: Vec<&str>, expires: i32) -> Result<Response, reqwest::Error> {
+pub fn create_alert(api_key: &ApiKey, name: &str, ips: Vec<&str>, expires: i32) -> Result<Alert, ShodanError> {
     /*
         Description:
             Create an alert to monitor a network range
             Use this method to create a network alert for a defined IP/ netblock which can be used to subscribe to changes/ events that are discovered within that range. 
             (POST /shodan/alert)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
             name (&str): The name to describe the network alert.
             ips (Vec<&str>): An object specifying the criteria that an alert should trigger. The only supported option at the moment is the "ip" filter. -  A list of IPs or network ranges defined using CIDR notation.
             expires (i32): Number of seconds that the alert should be active.
         Returns:
-            Result<Response, Box<dyn StdError>>: 
-                Ok(Response): Returns the result of the POST request containing the response.
-                Err(Box<dyn StdError>): Returns an error if the POST request fails.
+            Result<Alert, ShodanError>: 
+                Ok(Alert): Returns the created alert, decoded.
+                Err(ShodanError): Returns an error if the POST request fails or Shodan refuses to create the alert.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/alert?key={}", api_key);
+    let url = format!("https://api.shodan.io/shodan/alert?key={}", api_key.expose());
 
     let data_received = json!({
         "name": name,
@@ -263,16 +293,16 @@ pub fn create_alert(api_key: &str, name: &str, ips: Vec<&str>, expires: i32) ->
         String::new()
     });
 
-    let response = client
+    let request = client
         .post(url)
         .header("Content-Type", "application/json")
-        .body(alert_data.into_bytes())
-        .send()?;
+        .body(alert_data.into_bytes());
+    let response = helpers::send("create_alert", request);
 
-    Ok(response)
+    helpers::decode_response(response)
 }
 
-pub fn get_alert_info_id(alert_id: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_alert_info_id(alert_id: &str, api_key: &ApiKey) -> Result<Alert, ShodanError> {
     /*
         Description:
             Get the details for a network alert
@@ -280,19 +310,19 @@ pub fn get_alert_info_id(alert_id: &str, api_key: &str) -> Result<Response, Reqw
             (GET /shodan/alert/{id}/info)
         Parameters:
             alert_id (&str): The unique ID that was returned by /shodan/alert.
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
-            Result<Response, ReqwestError>: 
-                Ok(Response): Returns the result of the GET request containing the response.
-                Err(ReqwestError): Returns an error if the GET request fails.
+            Result<Alert, ShodanError>: 
+                Ok(Alert): Returns the alert, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/alert/{}/info?key={}", alert_id, api_key);
-    let response = client.get(&url).send()?;
-    Ok(response)
+    let url = format!("https://api.shodan.io/shodan/alert/{}/info?key={}", alert_id, api_key.expose());
+    let response = helpers::send("get_alert_info_id", client.get(&url));
+    helpers::decode_response(response)
 }
 
-pub fn delete_alert(alert_id: &str, api_key: &str) -> Result<Response, reqwest::Error> {
+pub fn delete_alert(alert_id: &str, api_key: &ApiKey) -> Result<Response, reqwest::Error> {
     /*
         Description:
             Delete an alert
@@ -300,20 +330,20 @@ pub fn delete_alert(alert_id: &str, api_key: &str) -> Result<Response, reqwest::
             (DELETE /shodan/alert/{id})
         Parameters:
             alert_id (&str): The unique ID that was returned by /shodan/alert.
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the DELETE request containing the response.
                 Err(ReqwestError): Returns an error if the DELETE request fails.
     */
-    let url = format!("https://api.shodan.io/shodan/alert/{}?key={}", alert_id, api_key);
+    let url = format!("https://api.shodan.io/shodan/alert/{}?key={}", alert_id, api_key.expose());
     let client = Client::new();
-    let response = client.delete(&url).send()?;
+    let response = helpers::send("delete_alert", client.delete(&url))?;
     Ok(response)
 }
 
 
-pub fn edit_alert(alert_id: &str, api_key: &str, ips: Vec<&str>) -> Result<Response, reqwest::Error> {
+pub fn edit_alert(alert_id: &str, api_key: &ApiKey, ips: Vec<&str>) -> Result<Alert, ShodanError> {
     /*
         Description:
             Edit the networks monitored in an alert
@@ -321,15 +351,15 @@ pub fn edit_alert(alert_id: &str, api_key: &str, ips: Vec<&str>) -> Result<Respo
             (POST /shodan/alert/{id})
         Parameters:
             alert_id (&str): The unique ID that was returned by /shodan/alert
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
             ips (Vec<&str>): An object specifying the criteria that an alert should trigger. The only supported option at the moment is the "ip" filter. -  A list of IPs or network ranges defined using CIDR notation.
         Returns:
-            Result<Response, Box<dyn StdError>>: 
-                Ok(Response): Returns the result of the POST request containing the response.
-                Err(Box<dyn StdError>): Returns an error if the POST request fails.
+            Result<Alert, ShodanError>: 
+                Ok(Alert): Returns the updated alert, decoded.
+                Err(ShodanError): Returns an error if the POST request fails or Shodan refuses to update the alert.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/alert/{}?key={}", alert_id, api_key);
+    let url = format!("https://api.shodan.io/shodan/alert/{}?key={}", alert_id, api_key.expose());
 
     let data_received = json!({
         "filters": {
@@ -342,54 +372,54 @@ pub fn edit_alert(alert_id: &str, api_key: &str, ips: Vec<&str>) -> Result<Respo
         String::new()
     });
 
-    let response = client
+    let request = client
         .post(url)
         .header("Content-Type", "application/json")
-        .body(alert_data.into_bytes())
-        .send()?;
+        .body(alert_data.into_bytes());
+    let response = helpers::send("edit_alert", request);
 
-    Ok(response)
+    helpers::decode_response(response)
 }
 
-pub fn get_alert_info(api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_alert_info(api_key: &ApiKey) -> Result<Vec<Alert>, ShodanError> {
     /*
         Description:
             Get a list of all the created alerts
             Returns a listing of all the network alerts that are currently active on the account
             (GET /shodan/alert/info)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
-            Result<Response, ReqwestError>: 
-                Ok(Response): Returns the result of the GET request containing the response.
-                Err(ReqwestError): Returns an error if the GET request fails.
+            Result<Vec<Alert>, ShodanError>: 
+                Ok(Vec<Alert>): Returns the alerts of the account, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/alert/info?key={}", api_key);
-    let response = client.get(&url).send()?;
-    Ok(response)
+    let url = format!("https://api.shodan.io/shodan/alert/info?key={}", api_key.expose());
+    let response = helpers::send("get_alert_info", client.get(&url));
+    helpers::decode_response(response)
 }
 
-pub fn get_alert_triggers(api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_alert_triggers(api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             Get a list of available triggers
             Returns a list of all the triggers that can be enabled on network alerts.
             (GET /shodan/alert/triggers)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/alert/triggers?key={}", api_key);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/alert/triggers?key={}", api_key.expose());
+    let response = helpers::send("get_alert_triggers", client.get(&url))?;
     Ok(response)
 }
 
-pub fn add_trigger(alert_id: &str, trigger: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn add_trigger(alert_id: &str, triggers: &[AlertTrigger], api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             Enable a trigger
@@ -397,20 +427,20 @@ pub fn add_trigger(alert_id: &str, trigger: &str, api_key: &str) -> Result<Respo
             (PUT /shodan/alert/{id}/trigger/{trigger})
         Parameters:
             alert_id (&str): The unique ID that was returned by /shodan/alert.
-            trigger (&str): Comma-separated list of trigger names
-            api_key (&str): Shodan API Key
+            triggers (&[AlertTrigger]): Triggers to enable, sent as a comma-separated list of trigger names
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the PUT request containing the response.
                 Err(ReqwestError): Returns an error if the PUT request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/alert/{}/trigger/{}?key={}", alert_id, trigger, api_key);
-    let response = client.put(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/alert/{}/trigger/{}?key={}", alert_id, AlertTrigger::join(triggers), api_key.expose());
+    let response = helpers::send("add_trigger", client.put(&url))?;
     Ok(response)
 }   
 
-pub fn delete_trigger(alert_id: &str, trigger: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn delete_trigger(alert_id: &str, triggers: &[AlertTrigger], api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             Disable a trigger
@@ -418,20 +448,20 @@ pub fn delete_trigger(alert_id: &str, trigger: &str, api_key: &str) -> Result<Re
             (DELETE /shodan/alert/{id}/trigger/{trigger})
         Parameters:
             alert_id (&str): The unique ID that was returned by /shodan/alert.
-            trigger (&str): Comma-separated list of trigger names
-            api_key (&str): Shodan API Key
+            triggers (&[AlertTrigger]): Triggers to disable, sent as a comma-separated list of trigger names
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the DELETE request containing the response.
                 Err(ReqwestError): Returns an error if the DELETE request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/alert/{}/trigger/{}?key={}", alert_id, trigger, api_key);
-    let response = client.delete(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/alert/{}/trigger/{}?key={}", alert_id, AlertTrigger::join(triggers), api_key.expose());
+    let response = helpers::send("delete_trigger", client.delete(&url))?;
     Ok(response)
 }   
 
-pub fn add_whitelist(alert_id: &str, trigger: &str, service: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn add_whitelist(alert_id: &str, trigger: &AlertTrigger, service: &str, api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             Add to Whitelist
@@ -439,21 +469,21 @@ pub fn add_whitelist(alert_id: &str, trigger: &str, service: &str, api_key: &str
             (PUT /shodan/alert/{id}/trigger/{trigger}/ignore/{service})
         Parameters:
             alert_id (&str): The unique ID that was returned by /shodan/alert.
-            trigger (&str): Trigger name
+            trigger (&AlertTrigger): Trigger the service should be ignored for
             service (&str): Service specified in the format "ip:port" (ex. "1.1.1.1:80")
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the PUT request containing the response.
                 Err(ReqwestError): Returns an error if the PUT request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/alert/{}/trigger/{}/ignore/{}?key={}", alert_id, trigger, service, api_key);
-    let response = client.put(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/alert/{}/trigger/{}/ignore/{}?key={}", alert_id, trigger, service, api_key.expose());
+    let response = helpers::send("add_whitelist", client.put(&url))?;
     Ok(response)
 }   
 
-pub fn delete_whitelist(alert_id: &str, trigger: &str, service: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn delete_whitelist(alert_id: &str, trigger: &AlertTrigger, service: &str, api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             Remove from Whitelist
@@ -461,21 +491,21 @@ pub fn delete_whitelist(alert_id: &str, trigger: &str, service: &str, api_key: &
             (DELETE /shodan/alert/{id}/trigger/{trigger}/ignore/{service})
         Parameters:
             alert_id (&str): The unique ID that was returned by /shodan/alert.
-            trigger (&str): Trigger name
+            trigger (&AlertTrigger): Trigger the service should no longer be ignored for
             service (&str): Service specified in the format "ip:port" (ex. "1.1.1.1:80")
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the DELETE request containing the response.
                 Err(ReqwestError): Returns an error if the DELETE request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/alert/{}/trigger/{}/ignore/{}?key={}", alert_id, trigger, service, api_key);
-    let response = client.delete(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/alert/{}/trigger/{}/ignore/{}?key={}", alert_id, trigger, service, api_key.expose());
+    let response = helpers::send("delete_whitelist", client.delete(&url))?;
     Ok(response)
 }  
 
-pub fn add_notifier_alert(alert_id: &str, notifier_id: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn add_notifier_alert(alert_id: &str, notifier_id: &str, api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             Add the notifier to the alert
@@ -485,19 +515,19 @@ pub fn add_notifier_alert(alert_id: &str, notifier_id: &str, api_key: &str) -> R
         Parameters:
             alert_id (&str): The unique ID that was returned by /shodan/alert.
             notifier_id (&str): Notifier ID
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the PUT request containing the response.
                 Err(ReqwestError): Returns an error if the PUT request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/alert/{}/notifier/{}?key={}", alert_id, notifier_id, api_key);
-    let response = client.put(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/alert/{}/notifier/{}?key={}", alert_id, notifier_id, api_key.expose());
+    let response = helpers::send("add_notifier_alert", client.put(&url))?;
     Ok(response)
 }  
 
-pub fn delete_notifier_alert(alert_id: &str, notifier_id: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn delete_notifier_alert(alert_id: &str, notifier_id: &str, api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             Remove the notifier from the alert
@@ -506,88 +536,99 @@ pub fn delete_notifier_alert(alert_id: &str, notifier_id: &str, api_key: &str) -
         Parameters:
             alert_id (&str): The unique ID that was returned by /shodan/alert.
             notifier_id (&str): Notifier ID
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the DELETE request containing the response.
                 Err(ReqwestError): Returns an error if the DELETE request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/alert/{}/notifier/{}?key={}", alert_id, notifier_id, api_key);
-    let response = client.delete(&url).send()?;
+    let url = format!("https://api.shodan.io/shodan/alert/{}/notifier/{}?key={}", alert_id, notifier_id, api_key.expose());
+    let response = helpers::send("delete_notifier_alert", client.delete(&url))?;
     Ok(response)
 }  
 
-pub fn get_notifier(api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_notifier(api_key: &ApiKey) -> Result<Vec<Notifier>, ShodanError> {
     /*
         Description:
             List all user-created notifiers
             Get a list of all the notifiers that the user has created.
             (GET /notifier)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
-            Result<Response, ReqwestError>: 
-                Ok(Response): Returns the result of the GET request containing the response.
-                Err(ReqwestError): Returns an error if the GET request fails.
+            Result<Vec<Notifier>, ShodanError>: 
+                Ok(Vec<Notifier>): Returns the notifiers of the account, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/notifier?key={}", api_key);
-    let response = client.get(&url).send()?;
-    Ok(response)
+    let url = format!("https://api.shodan.io/notifier?key={}", api_key.expose());
+    let response = helpers::send("get_notifier", client.get(&url));
+    let listing: NotifierListing = helpers::decode_response(response)?;
+    Ok(listing.matches)
 }
 
-pub fn get_notifier_provider(api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_notifier_provider(api_key: &ApiKey) -> Result<Vec<NotifierProvider>, ShodanError> {
     /*
         Description:
             List of available notification providers
             Get a list of all the notification providers that are available and the parameters to submit when creating them.
             (GET /notifier/provider)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
-            Result<Response, ReqwestError>: 
-                Ok(Response): Returns the result of the GET request containing the response.
-                Err(ReqwestError): Returns an error if the GET request fails.
+            Result<Vec<NotifierProvider>, ShodanError>: 
+                Ok(Vec<NotifierProvider>): Returns the available providers with their required arguments, sorted by name.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/notifier/provider?key={}", api_key);
-    let response = client.get(&url).send()?;
-    Ok(response)
+    let url = format!("https://api.shodan.io/notifier/provider?key={}", api_key.expose());
+    let response = helpers::send("get_notifier_provider", client.get(&url));
+    let providers: HashMap<String, NotifierProvider> = helpers::decode_response(response)?;
+    let mut providers: Vec<NotifierProvider> = providers
+        .into_iter()
+        .map(|(name, provider)| NotifierProvider { name, ..provider })
+        .collect();
+    providers.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(providers)
 }
 
-pub fn create_notifier(api_key: &str, provider: &str, description: &str, argument: &str, argument_value: &str) -> Result<Response, reqwest::Error> {
+pub fn create_notifier(api_key: &ApiKey, description: &str, config: &NotifierConfig) -> Result<Notifier, ShodanError> {
     /*
         Description:
             Create a new notification service for the user
             Use this method to create a new notification service endpoint that Shodan services can send notifications through.
             (POST /notifier)
         Parameters:
-            api_key (&str): Shodan API Key.
-            provider (&str): Provider name as returned by /notifier/provider
+            api_key (&ApiKey): Shodan API Key.
             description (&str): Description of the notifier
-            argument (&str): Argument required by the provider (ex: to)
-            argument_value (&str): Value of the argument required by the provider (ex: jmath@shodan.io) 
+            config (&NotifierConfig): Provider and every argument it requires (ex: NotifierConfig::Email { to: "jmath@shodan.io" }),
+            see notifiers::validate_notifier_config() to check it against (GET /notifier/provider) first.
         Returns:
-            Result<Response, reqwest::Error>:
-                Ok(Response): Returns the result of the POST request containing the response.
-                Err(reqwest::Error): Returns an error if the POST request fails.
+            Result<Notifier, ShodanError>:
+                Ok(Notifier): Returns the created notifier (Shodan only answers with its ID, the rest is what was sent).
+                Err(ShodanError): Returns an error if the POST request fails or Shodan refuses to create the notifier.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/notifier?key={}", api_key);
-    let response = client
-        .post(url)
-        .form(&[
-            ("provider", provider),
-            ("description", description),
-            (argument, argument_value),
-        ])
-        .send()?;
+    let url = format!("https://api.shodan.io/notifier?key={}", api_key.expose());
 
-    Ok(response)
+    let args = config.args();
+    let mut form = args.clone();
+    form.insert("provider".to_string(), config.provider().to_string());
+    form.insert("description".to_string(), description.to_string());
+
+    let response = helpers::send("create_notifier", client.post(url).form(&form));
+    let created: CreatedNotifier = helpers::decode_response(response)?;
+
+    Ok(Notifier {
+        id: created.id,
+        provider: config.provider().to_string(),
+        description: Some(description.to_string()),
+        args: args.into_iter().map(|(name, value)| (name, Value::String(value))).collect(),
+    })
 }
 
-pub fn delete_notifier(notifier_id: &str, api_key: &str) -> Result<Response, reqwest::Error> {
+pub fn delete_notifier(notifier_id: &str, api_key: &ApiKey) -> Result<Response, reqwest::Error> {
     /*
         Description:
            Delete a notification service
@@ -595,19 +636,19 @@ pub fn delete_notifier(notifier_id: &str, api_key: &str) -> Result<Response, req
             (DELETE /notifier/{id})
         Parameters:
             notifier_id (&str): Notifier ID returned by (POST /notifier)
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
         Result<Response, ReqwestError>: 
             Ok(Response): Returns the result of the DELETE request containing the response.
             Err(ReqwestError): Returns an error if the DELETE request fails.
     */
-    let url = format!("https://api.shodan.io/notifier/{}?key={}", notifier_id, api_key);
+    let url = format!("https://api.shodan.io/notifier/{}?key={}", notifier_id, api_key.expose());
     let client = Client::new();
-    let response = client.delete(&url).send()?;
+    let response = helpers::send("delete_notifier", client.delete(&url))?;
     Ok(response)
 }
 
-pub fn get_notifier_info(notifier_id: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_notifier_info(notifier_id: &str, api_key: &ApiKey) -> Result<Notifier, ShodanError> {
     /*
         Description:
             Get information about a notifier
@@ -615,123 +656,130 @@ pub fn get_notifier_info(notifier_id: &str, api_key: &str) -> Result<Response, R
             (GET /notifier/{id})
         Parameters:
             notifier_id (&str): Notifier ID returned by (POST /notifier)
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
-            Result<Response, ReqwestError>: 
-                Ok(Response): Returns the result of the GET request containing the response.
-                Err(ReqwestError): Returns an error if the GET request fails.
+            Result<Notifier, ShodanError>: 
+                Ok(Notifier): Returns the notifier, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/notifier/{}?key={}", notifier_id, api_key);
-    let response = client.get(&url).send()?;
-    Ok(response)
+    let url = format!("https://api.shodan.io/notifier/{}?key={}", notifier_id, api_key.expose());
+    let response = helpers::send("get_notifier_info", client.get(&url));
+    helpers::decode_response(response)
 }
 
-pub fn edit_notifier(notifier_id: &str, api_key: &str, argument: &str, argument_value: &str) -> Result<Response, ReqwestError> {
+pub fn edit_notifier(notifier_id: &str, api_key: &ApiKey, args: &HashMap<String, String>) -> Result<Notifier, ShodanError> {
     /*
     Description:
         Edit a notifier
         Use this method to update the parameters of a notifier.
-        (PUT /notifier/{id})
+        (PUT /notifier/{id}), then (GET /notifier/{id}) to return the notifier as Shodan now stores it.
     Parameters:
         notifier_id (&str): Notifier ID returned by (POST /notifier)
-        api_key (&str): Shodan API Key
-        argument (&str): Argument required by the provider (ex: to)
-        argument_value (&str): Value of the argument required by the provider (ex: jmath@shodan.io) 
+        api_key (&ApiKey): Shodan API Key
+        args (&HashMap<String, String>): Every argument required by the provider with its new value (ex: {"to": "jmath@shodan.io"}),
+        NotifierConfig::args() builds this map from a typed config.
     Returns:
-        Result<Response, ReqwestError>: 
-            Ok(Response): Returns the result of the PUT request containing the response.
-            Err(ReqwestError): Returns an error if the PUT request fails.
+        Result<Notifier, ShodanError>: 
+            Ok(Notifier): Returns the edited notifier, decoded.
+            Err(ShodanError): Returns an error if one of the requests fails or Shodan refuses the new arguments.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/notifier/{}?key={}", notifier_id, api_key);
-    let response = client.put(url).form(&[(argument, argument_value)]).send()?;
-    Ok(response)
+    let url = format!("https://api.shodan.io/notifier/{}?key={}", notifier_id, api_key.expose());
+    helpers::json_from_response(helpers::send("edit_notifier", client.put(url).form(args)))?;
+    get_notifier_info(notifier_id, api_key)
 }
 
-pub fn get_query(page: &str, sort: &str, order: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_query(page: u32, sort: Option<SortBy>, order: Option<Order>, api_key: &ApiKey) -> Result<SavedQueryPage, ShodanError> {
     /*
         Description:
             List the saved search queries
             Use this method to obtain a list of search queries that users have saved in Shodan.
             (GET /shodan/query)
         Parameters:
-            page (optional) (&str): Page number to iterate over results; each page contains 10 items
-            sort (optional) (&str): Sort the list based on a property. Possible values are: votes, timestamp
-            order (optional) (&str): Whether to sort the list in ascending or descending order. Possible values are: asc, desc
-            api_key (&str): Shodan API Key
+            page (u32): Page number to iterate over results, starting at 1; each page contains 10 items (see queries::SavedQueries to walk every page)
+            sort (Option<SortBy>): Sort the list based on a property, None for the Shodan default
+            order (Option<Order>): Whether to sort the list in ascending or descending order, None for the Shodan default
+            api_key (&ApiKey): Shodan API Key
         Returns:
-            Result<Response, ReqwestError>: 
-                Ok(Response): Returns the result of the GET request containing the response.
-                Err(ReqwestError): Returns an error if the GET request fails.
+            Result<SavedQueryPage, ShodanError>: 
+                Ok(SavedQueryPage): Returns the requested page, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/query?page={}&sort={}&order={}&key={}", page, sort, order, api_key);
-    let response = client.get(&url).send()?;
-    Ok(response)
+    let mut url = format!("https://api.shodan.io/shodan/query?page={}&key={}", page, api_key.expose());
+    if let Some(sort) = sort {
+        url.push_str(&format!("&sort={}", sort));
+    }
+    if let Some(order) = order {
+        url.push_str(&format!("&order={}", order));
+    }
+    let response = helpers::send("get_query", client.get(&url));
+    helpers::decode_response(response)
 }
 
-pub fn get_query_search(query: &str, page: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_query_search(query: &str, page: u32, api_key: &ApiKey) -> Result<SavedQueryPage, ShodanError> {
     /*
         Description:
             Search the directory of saved search queries.
             Use this method to search the directory of search queries that users have saved in Shodan.
             (GET /shodan/query/search)
         Parameters:
-            query: [String] What to search for in the directory of saved search queries.
-            page (optional) (&str): Page number to iterate over results; each page contains 10 items
-            api_key (&str): Shodan API Key
+            query (&str): What to search for in the directory of saved search queries.
+            page (u32): Page number to iterate over results, starting at 1; each page contains 10 items (see queries::SavedQueries to walk every page)
+            api_key (&ApiKey): Shodan API Key
         Returns:
-            Result<Response, ReqwestError>: 
-                Ok(Response): Returns the result of the GET request containing the response.
-                Err(ReqwestError): Returns an error if the GET request fails.
+            Result<SavedQueryPage, ShodanError>: 
+                Ok(SavedQueryPage): Returns the requested page, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/query/search?query={}&page={}&key={}", query, page, api_key);
-    let response = client.get(&url).send()?;
-    Ok(response)
+    let url = format!("https://api.shodan.io/shodan/query/search?page={}&key={}", page, api_key.expose());
+    let response = helpers::send("get_query_search", client.get(&url).query(&[("query", query)]));
+    helpers::decode_response(response)
 }
 
-pub fn get_query_tags(size: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_query_tags(size: u32, api_key: &ApiKey) -> Result<Vec<QueryTag>, ShodanError> {
     /*
         Description:
             List the most popular tags
             Use this method to obtain a list of popular tags for the saved search queries in Shodan.
             (GET /shodan/query/tags)
         Parameters:
-            size (optional): [Integer] The number of tags to return (default: 10).
-            api_key (&str): Shodan API Key
+            size (u32): The number of tags to return (Shodan default: 10).
+            api_key (&ApiKey): Shodan API Key
         Returns:
-            Result<Response, ReqwestError>: 
-                Ok(Response): Returns the result of the GET request containing the response.
-                Err(ReqwestError): Returns an error if the GET request fails.
+            Result<Vec<QueryTag>, ShodanError>: 
+                Ok(Vec<QueryTag>): Returns the tags, most used first.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/shodan/query/tags?size={}&key={}", size, api_key);
-    let response = client.get(&url).send()?;
-    Ok(response)
+    let url = format!("https://api.shodan.io/shodan/query/tags?size={}&key={}", size, api_key.expose());
+    let response = helpers::send("get_query_tags", client.get(&url));
+    let listing: QueryTagListing = helpers::decode_response(response)?;
+    Ok(listing.matches)
 }
 
-pub fn get_account_profile(api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_account_profile(api_key: &ApiKey) -> Result<AccountProfile, ShodanError> {
     /*
         Description:
             Account Profile
             Returns information about the Shodan account linked to this API key.
             (GET /account/profile)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
-            Result<Response, ReqwestError>: 
-                Ok(Response): Returns the result of the GET request containing the response.
-                Err(ReqwestError): Returns an error if the GET request fails.
+            Result<AccountProfile, ShodanError>: 
+                Ok(AccountProfile): Returns the account profile, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/account/profile?key={}", api_key);
-    let response = client.get(&url).send()?;
-    Ok(response)
+    let url = format!("https://api.shodan.io/account/profile?key={}", api_key.expose());
+    let response = helpers::send("get_account_profile", client.get(&url));
+    helpers::decode_response(response)
 }
 
-pub fn get_dns_domain(domain: &str, history: &str, type_: &str, page: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_dns_domain(domain: &str, history: bool, type_: Option<&DnsRecordType>, page: u32, api_key: &ApiKey) -> Result<DomainInfo, ShodanError> {
     /*
         Description:
             Domain Information
@@ -739,22 +787,26 @@ pub fn get_dns_domain(domain: &str, history: &str, type_: &str, page: &str, api_
             (GET /dns/domain/{domain})
         Parameters:
             domain: (&str) Domain name to lookup; example "cnn.com"
-            history (optional) (&str): True if historical DNS data should be included in the results (default: False)
-            type (optional) (&str):  DNS type, possible values are: A, AAAA, CNAME, NS, SOA, MX, TXT
-            page (optional) (&str): The page number to page through results 100 at a time (default: 1)
-            api_key (&str): Shodan API Key
+            history (bool): True if historical DNS data should be included in the results
+            type_ (Option<&DnsRecordType>): Only return the records of this DNS type, None for every type
+            page (u32): The page number to page through results 100 at a time, starting at 1 (see dns::DomainRecords to walk every page)
+            api_key (&ApiKey): Shodan API Key
         Returns:
-            Result<Response, ReqwestError>: 
-                Ok(Response): Returns the result of the GET request containing the response.
-                Err(ReqwestError): Returns an error if the GET request fails.
+            Result<DomainInfo, ShodanError>: 
+                Ok(DomainInfo): Returns the requested page, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/dns/domain/{}?history={}&type={}&page={}&key={}", domain, history, type_, page, api_key);
-    let response = client.get(&url).send()?;
-    Ok(response)
+    let history = if history { "True" } else { "False" };
+    let mut url = format!("https://api.shodan.io/dns/domain/{}?history={}&page={}&key={}", domain, history, page, api_key.expose());
+    if let Some(type_) = type_ {
+        url.push_str(&format!("&type={}", type_));
+    }
+    let response = helpers::send("get_dns_domain", client.get(&url));
+    helpers::decode_response(response)
 }
 
-pub fn get_dns_resolve(hostname: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_dns_resolve(hostname: &str, api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             DNS Lookup
@@ -762,19 +814,19 @@ pub fn get_dns_resolve(hostname: &str, api_key: &str) -> Result<Response, Reqwes
             (GET /dns/resolve)
         Parameters:
             hostnames (&str): Comma-separated list of hostnames; example "google.com,bing.com"
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/dns/resolve?hostnames={}&key={}", hostname, api_key);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/dns/resolve?key={}", api_key.expose());
+    let response = helpers::send("get_dns_resolve", client.get(&url).query(&[("hostnames", hostname)]))?;
     Ok(response)
 }
 
-pub fn get_dns_reverse(ips: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_dns_reverse(ips: &str, api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             Reverse DNS Lookup
@@ -782,95 +834,117 @@ pub fn get_dns_reverse(ips: &str, api_key: &str) -> Result<Response, ReqwestErro
             (GET /dns/reverse)
         Parameters:
             ips (&str): Comma-separated list of IP addresses; example "74.125.227.230,204.79.197.200"
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/dns/reverse?ips={}&key={}", ips, api_key);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/dns/reverse?key={}", api_key.expose());
+    let response = helpers::send("get_dns_reverse", client.get(&url).query(&[("ips", ips)]))?;
     Ok(response)
 }
 
-pub fn get_tools_headers(api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_tools_headers(api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             HTTP Headers
             Shows the HTTP headers that your client sends when connecting to a webserver.
             (GET /tools/httpheaders)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/tools/httpheaders?key={}", api_key);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/tools/httpheaders?key={}", api_key.expose());
+    let response = helpers::send("get_tools_headers", client.get(&url))?;
     Ok(response)
 }
 
-pub fn get_tools_myip(api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_tools_myip(api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
             My IP Address
             Get your current IP address as seen from the Internet.
             (GET /tools/myip)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/tools/myip?key={}", api_key);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/tools/myip?key={}", api_key.expose());
+    let response = helpers::send("get_tools_myip", client.get(&url))?;
     Ok(response)
 }
 
-pub fn get_api_info(api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_labs_honeyscore(ip: &str, api_key: &ApiKey) -> Result<Response, ReqwestError> {
     /*
         Description:
-            API Plan Information
-            Returns information about the API plan belonging to the given API key.
-            (GET /api-info)
+            Calculate Honeypot Probability Score
+            Calculates a honeypot probability score ranging from 0 (not a honeypot) to 1.0 (is a honeypot).
+            (GET /labs/honeyscore/{ip})
         Parameters:
-            api_key (&str): Shodan API Key
+            ip (&str): Host IP address
+            api_key (&ApiKey): Shodan API Key
         Returns:
             Result<Response, ReqwestError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
                 Err(ReqwestError): Returns an error if the GET request fails.
     */
     let client = Client::new();
-    let url = format!("https://api.shodan.io/api-info?key={}", api_key);
-    let response = client.get(&url).send()?;
+    let url = format!("https://api.shodan.io/labs/honeyscore/{}?key={}", ip, api_key.expose());
+    let response = helpers::send("get_labs_honeyscore", client.get(&url))?;
     Ok(response)
 }
 
-pub fn get_all_network_alerts(api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_api_info(api_key: &ApiKey) -> Result<ApiInfo, ShodanError> {
+    /*
+        Description:
+            API Plan Information
+            Returns information about the API plan belonging to the given API key.
+            (GET /api-info)
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+        Returns:
+            Result<ApiInfo, ShodanError>: 
+                Ok(ApiInfo): Returns the plan, credits and usage limits, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
+    */
+    let client = Client::new();
+    let url = format!("https://api.shodan.io/api-info?key={}", api_key.expose());
+    let response = helpers::send("get_api_info", client.get(&url));
+    helpers::decode_response(response)
+}
+
+pub fn get_all_network_alerts(api_key: &ApiKey, info: &ApiInfo) -> Result<Response, ShodanError> {
     /*
         Description:
             All Network Alerts
             Subscribe to banners discovered on all IP ranges described in the network alerts. The network alerts are renewed periodically every 1 hour.
             (GET /shodan/alert)
         Parameters:
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
+            info (&ApiInfo): Plan of the API key, from get_api_info(). The stream is refused locally when the plan has no network alerts.
         Returns:
-            Result<Response, ReqwestError>: 
+            Result<Response, ShodanError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
-                Err(ReqwestError): Returns an error if the GET request fails.
+                Err(ShodanError): Returns an error if the plan is too limited or the GET request fails.
     */
+    plans::require(info, Capability::NetworkAlerts)?;
     let client = Client::builder().timeout(None).build()?;
-    let url = format!("https://stream.shodan.io/shodan/alert?key={}", api_key);
-    let response = client.get(&url).send()?;
+    let url = format!("https://stream.shodan.io/shodan/alert?key={}", api_key.expose());
+    let response = helpers::send("get_all_network_alerts", client.get(&url))?;
     Ok(response)
 }
 
-pub fn get_all_network_alert_for_alert_id(alert_id: &str, api_key: &str) -> Result<Response, ReqwestError> {
+pub fn get_all_network_alert_for_alert_id(alert_id: &str, api_key: &ApiKey, info: &ApiInfo) -> Result<Response, ShodanError> {
     /*
         Description:
             Filtered by Alert ID
@@ -878,14 +952,16 @@ pub fn get_all_network_alert_for_alert_id(alert_id: &str, api_key: &str) -> Resu
             (GET /shodan/alert/{id})
         Parameters:
             alert_id (&str): The unique ID that was returned by (POST /shodan/alert) in the Network Alerts REST API.
-            api_key (&str): Shodan API Key
+            api_key (&ApiKey): Shodan API Key
+            info (&ApiInfo): Plan of the API key, from get_api_info(). The stream is refused locally when the plan has no network alerts.
         Returns:
-            Result<Response, ReqwestError>: 
+            Result<Response, ShodanError>: 
                 Ok(Response): Returns the result of the GET request containing the response.
-                Err(ReqwestError): Returns an error if the GET request fails.
+                Err(ShodanError): Returns an error if the plan is too limited or the GET request fails.
     */
+    plans::require(info, Capability::NetworkAlerts)?;
     let client = Client::builder().timeout(None).build()?;
-    let url = format!("https://stream.shodan.io/shodan/alert/{}?key={}", alert_id, api_key);
-    let response = client.get(&url).send()?;
+    let url = format!("https://stream.shodan.io/shodan/alert/{}?key={}", alert_id, api_key.expose());
+    let response = helpers::send("get_all_network_alert_for_alert_id", client.get(&url))?;
     Ok(response)
 }
\ No newline at end of file
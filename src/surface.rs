@@ -0,0 +1,251 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use serde::Serialize;
+use crate::dns::{self, DomainRecords};
+use crate::credentials::ApiKey;
+use crate::error::ShodanError;
+use crate::helpers::{self, RateLimiter};
+use crate::models::{DnsRecord, DnsRecordType, HostInfo};
+use crate::shodan;
+
+/*
+    Description:
+        External attack-surface map of a domain: domain -> subdomains -> IPs -> open ports and services.
+        Built by build_attack_surface(), exported with to_json() or to_dot() (Graphviz).
+*/
+#[derive(Debug, Clone, Serialize)]
+pub struct AttackSurface {
+    pub domain: String,
+    pub subdomains: BTreeMap<String, SubdomainNode>,
+    pub hosts: BTreeMap<String, HostNode>,
+    pub credits_used: u32,
+}
+
+/*
+    Description:
+        A, AAAA and CNAME records of one subdomain (the key of AttackSurface::subdomains is the full hostname)
+        and the IPs it points to, either from its records or from (GET /dns/resolve) when it had no A/AAAA record.
+*/
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SubdomainNode {
+    pub records: Vec<DnsRecord>,
+    pub ips: BTreeSet<String>,
+}
+
+/*
+    Description:
+        What Shodan knows about one IP of the map. error is set when the host lookup failed
+        (ex: "No information available for that IP."), services is then empty.
+*/
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HostNode {
+    pub org: Option<String>,
+    pub services: Vec<ServiceNode>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceNode {
+    pub port: u16,
+    pub transport: Option<String>,
+    pub product: Option<String>,
+    pub version: Option<String>,
+}
+
+impl AttackSurface {
+    pub fn to_json(&self) -> Result<String, ShodanError> {
+        /*
+            Description:
+                Returns the map as pretty-printed JSON.
+        */
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn to_dot(&self) -> String {
+        /*
+            Description:
+                Returns the map as a Graphviz DOT graph (ex: `dot -Tsvg map.dot -o map.svg`).
+        */
+        let mut dot = String::new();
+        let _ = writeln!(dot, "digraph {} {{", dot_id(&self.domain));
+        let _ = writeln!(dot, "    rankdir=LR;");
+        let _ = writeln!(dot, "    {} [shape=doubleoctagon];", dot_id(&self.domain));
+
+        for (name, subdomain) in &self.subdomains {
+            if name != &self.domain {
+                let _ = writeln!(dot, "    {} [shape=box];", dot_id(name));
+                let _ = writeln!(dot, "    {} -> {};", dot_id(&self.domain), dot_id(name));
+            }
+            for record in &subdomain.records {
+                if record.record_type == DnsRecordType::Cname {
+                    let _ = writeln!(dot, "    {} -> {} [label=\"CNAME\", style=dashed];", dot_id(name), dot_id(&record.value));
+                }
+            }
+            for ip in &subdomain.ips {
+                let _ = writeln!(dot, "    {} -> {};", dot_id(name), dot_id(ip));
+            }
+        }
+
+        for (ip, host) in &self.hosts {
+            let label = match &host.org {
+                Some(org) => format!("{}\n{}", ip, org),
+                None => ip.clone(),
+            };
+            let _ = writeln!(dot, "    {} [shape=ellipse, label={}];", dot_id(ip), dot_id(&label));
+            for service in &host.services {
+                let service_id = format!("{}:{}/{}", ip, service.port, service.transport.as_deref().unwrap_or("tcp"));
+                let mut label = format!("{}/{}", service.port, service.transport.as_deref().unwrap_or("tcp"));
+                if let Some(product) = &service.product {
+                    label.push('\n');
+                    label.push_str(product);
+                    if let Some(version) = &service.version {
+                        label.push(' ');
+                        label.push_str(version);
+                    }
+                }
+                let _ = writeln!(dot, "    {} [shape=note, label={}];", dot_id(&service_id), dot_id(&label));
+                let _ = writeln!(dot, "    {} -> {};", dot_id(ip), dot_id(&service_id));
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+pub fn build_attack_surface(api_key: &ApiKey, domain: &str) -> Result<AttackSurface, ShodanError> {
+    /*
+        Description:
+            Attack-surface map of a domain
+            1. Pages through every DNS record of the domain (GET /dns/domain/{domain}), keeping the A, AAAA and CNAME records.
+            2. Resolves the subdomains without any A/AAAA record through (GET /dns/resolve), see dns::resolve_hostnames().
+            3. Looks up every unique IP with (GET /shodan/host/{ip}) to get its open ports and services.
+            WARNING ! -> Uses 1 query credit per page of 100 DNS records, the requests are rate limited to 1 per second.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            domain (&str): Domain name to map; example "cnn.com"
+        Returns:
+            Result<AttackSurface, ShodanError>:
+                Ok(AttackSurface): Returns the map (a failing host lookup is recorded in its HostNode, not returned as an error).
+                Err(ShodanError): Returns an error if a DNS request fails.
+    */
+    let mut subdomains: BTreeMap<String, SubdomainNode> = BTreeMap::new();
+    subdomains.insert(domain.to_string(), SubdomainNode::default());
+
+    let mut records = DomainRecords::new(api_key, domain, false, None);
+    for record in records.by_ref() {
+        let record = record?;
+        if !matches!(record.record_type, DnsRecordType::A | DnsRecordType::Aaaa | DnsRecordType::Cname) {
+            continue;
+        }
+        let node = subdomains.entry(full_hostname(&record.subdomain, domain)).or_default();
+        if record.record_type != DnsRecordType::Cname {
+            node.ips.insert(record.value.clone());
+        }
+        node.records.push(record);
+    }
+    for subdomain in records.subdomains() {
+        subdomains.entry(full_hostname(subdomain, domain)).or_default();
+    }
+    let credits_used = records.credits_used();
+
+    let mut limiter = RateLimiter::default();
+
+    let unresolved: Vec<String> = subdomains
+        .iter()
+        .filter(|(_, node)| node.ips.is_empty())
+        .map(|(name, _)| name.clone())
+        .collect();
+    for (name, ip) in dns::resolve_hostnames(api_key, unresolved, &mut limiter)? {
+        if let (Some(node), Some(ip)) = (subdomains.get_mut(&name), ip) {
+            node.ips.insert(ip.to_string());
+        }
+    }
+
+    let ips: BTreeSet<String> = subdomains.values().flat_map(|node| node.ips.iter().cloned()).collect();
+    let mut hosts = BTreeMap::new();
+    for ip in ips {
+        limiter.wait();
+        let node = match helpers::decode_response::<HostInfo>(shodan::get_host_ip(&ip, api_key)) {
+            Ok(host) => HostNode {
+                org: host.org,
+                services: host
+                    .data
+                    .into_iter()
+                    .map(|banner| ServiceNode {
+                        port: banner.port,
+                        transport: banner.transport,
+                        product: banner.product,
+                        version: banner.version,
+                    })
+                    .collect(),
+                error: None,
+            },
+            Err(err) => HostNode { error: Some(err.to_string()), ..HostNode::default() },
+        };
+        hosts.insert(ip, node);
+    }
+
+    Ok(AttackSurface { domain: domain.to_string(), subdomains, hosts, credits_used })
+}
+
+fn full_hostname(subdomain: &str, domain: &str) -> String {
+    if subdomain.is_empty() {
+        domain.to_string()
+    } else {
+        format!("{}.{}", subdomain, domain)
+    }
+}
+
+fn dot_id(text: &str) -> String {
+    // Quoted DOT ID: only '"' and '\' need escaping, a line break is written as Graphviz's \n (a new line in labels)
+    // and other control characters, which Graphviz can't read back, become spaces
+    let mut id = String::with_capacity(text.len() + 2);
+    id.push('"');
+    for c in text.chars() {
+        match c {
+            '"' | '\\' => {
+                id.push('\\');
+                id.push(c);
+            }
+            '\n' => id.push_str("\\n"),
+            c if c.is_control() => id.push(' '),
+            c => id.push(c),
+        }
+    }
+    id.push('"');
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dot_id_escapes_for_graphviz() {
+        assert_eq!(dot_id("www.example.com"), "\"www.example.com\"");
+        assert_eq!(dot_id("a\"b\\c"), "\"a\\\"b\\\\c\"");
+        assert_eq!(dot_id("1.2.3.4\nACME"), "\"1.2.3.4\\nACME\"");
+        assert_eq!(dot_id("a\tb\u{1}c\r"), "\"a b c \"");
+    }
+
+    #[test]
+    fn to_dot_escapes_hostnames() {
+        let mut subdomains = BTreeMap::new();
+        subdomains.insert(
+            "evil\"\nname.example.com".to_string(),
+            SubdomainNode { records: Vec::new(), ips: BTreeSet::from(["1.2.3.4".to_string()]) },
+        );
+        let mut hosts = BTreeMap::new();
+        hosts.insert("1.2.3.4".to_string(), HostNode { org: Some("ACME \"Corp\"".to_string()), ..HostNode::default() });
+        let surface = AttackSurface { domain: "example.com".to_string(), subdomains, hosts, credits_used: 0 };
+
+        let dot = surface.to_dot();
+        assert!(dot.contains("\"example.com\" -> \"evil\\\"\\nname.example.com\";"), "{}", dot);
+        assert!(dot.contains("\"evil\\\"\\nname.example.com\" -> \"1.2.3.4\";"), "{}", dot);
+        assert!(dot.contains("label=\"1.2.3.4\\nACME \\\"Corp\\\"\"];"), "{}", dot);
+        assert!(!dot.contains("\\u"), "{}", dot);
+        // Every line is a statement, no raw line break leaked from a name
+        assert!(dot.lines().skip(1).all(|line| line == "}" || line.ends_with(';')), "{}", dot);
+    }
+}
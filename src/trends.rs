@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::fs;
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::credentials::ApiKey;
+use crate::error::ShodanError;
+use crate::helpers;
+use crate::models::FacetValue;
+
+/*
+    Description:
+        Number of results matching a query during one month (ex: {"month": "2023-06", "count": 1234}).
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendPoint {
+    pub month: String,
+    pub count: u64,
+}
+
+/*
+    Description:
+        Top values of a facet during one month, key being the month (ex: "2023-06").
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendFacetBucket {
+    pub key: String,
+    #[serde(default)]
+    pub values: Vec<FacetValue>,
+}
+
+/*
+    Description:
+        Result of search_trends(): the monthly counts of a query, oldest month first, and the monthly top values of every
+        requested facet. Exported with to_csv() and facet_to_csv().
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendSeries {
+    #[serde(default)]
+    pub total: u64,
+    #[serde(default)]
+    pub matches: Vec<TrendPoint>,
+    #[serde(default)]
+    pub facets: HashMap<String, Vec<TrendFacetBucket>>,
+}
+
+impl TrendSeries {
+    pub fn to_csv(&self) -> String {
+        /*
+            Description:
+                Returns the monthly counts as CSV, with a "month,count" header.
+        */
+        let mut csv = String::from("month,count\n");
+        for point in &self.matches {
+            csv.push_str(&format!("{},{}\n", helpers::csv_field(&point.month), point.count));
+        }
+        csv
+    }
+
+    pub fn facet_to_csv(&self, facet: &str) -> Option<String> {
+        /*
+            Description:
+                Returns the monthly top values of a facet as CSV, with a "month,value,count" header and one row per month and value.
+                None if the facet was not requested.
+        */
+        let buckets = self.facets.get(facet)?;
+        let mut csv = String::from("month,value,count\n");
+        for bucket in buckets {
+            for facet_value in &bucket.values {
+                let value = match &facet_value.value {
+                    Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                csv.push_str(&format!(
+                    "{},{},{}\n",
+                    helpers::csv_field(&bucket.key),
+                    helpers::csv_field(&value),
+                    facet_value.count
+                ));
+            }
+        }
+        Some(csv)
+    }
+}
+
+pub fn search_trends(api_key: &ApiKey, query: &str, facets: &str) -> Result<TrendSeries, ShodanError> {
+    /*
+        Description:
+            Search Historical Trends
+            Returns the number of results matching a query for every month Shodan has data for, and the monthly top values
+            of the requested facets (ex: "product:mongodb asn:AS15169" with "country,org").
+            (GET https://trends.shodan.io/api/v1/search)
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            query (&str): Shodan search query, with the same filters as (GET /shodan/host/search)
+            facets (&str): A comma-separated list of properties to get monthly summary information on (ex: "country:5"), "" for none
+        Returns:
+            Result<TrendSeries, ShodanError>:
+                Ok(TrendSeries): Returns the monthly counts and facets, decoded.
+                Err(ShodanError): Returns an error if the GET request fails or returns an unexpected body.
+    */
+    let client = Client::new();
+    let request = client
+        .get("https://trends.shodan.io/api/v1/search")
+        .query(&[("query", query), ("facets", facets), ("key", api_key.expose())]);
+    let response = helpers::send("search_trends", request);
+    helpers::decode_response(response)
+}
+
+pub fn write_trends_csv(path: &str, series: &TrendSeries, facet: Option<&str>) -> Result<(), ShodanError> {
+    /*
+        Description:
+            Writes the monthly counts of a series (facet None) or the monthly values of one of its facets to a CSV file.
+        Parameters:
+            path (&str): The path of the file to write.
+            series (&TrendSeries): Series returned by search_trends().
+            facet (Option<&str>): The facet to export (ex: "country"), None for the monthly counts.
+        Returns:
+            Result<(), ShodanError>:
+                Ok(()): The file was written.
+                Err(ShodanError): Returns an error if the facet was not requested or writing the file fails.
+    */
+    let csv = match facet {
+        Some(facet) => series
+            .facet_to_csv(facet)
+            .ok_or_else(|| ShodanError::Invalid(format!("the series has no \"{}\" facet", facet)))?,
+        None => series.to_csv(),
+    };
+    fs::write(path, csv)?;
+    Ok(())
+}
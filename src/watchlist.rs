@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::credentials::ApiKey;
+use crate::error::ShodanError;
+use crate::helpers::{self, RateLimiter};
+use crate::models::{FacetValue, SavedQuery, SearchResults};
+use crate::shodan;
+
+/*
+    Description:
+        Query of a watchlist and when to alert on it:
+            threshold: alert when the count goes from below to at least threshold, or back below it.
+            change_percent: alert when the count changes by more than change_percent % since the previous run.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchEntry {
+    pub title: String,
+    pub query: String,
+    #[serde(default)]
+    pub threshold: Option<u64>,
+    #[serde(default)]
+    pub change_percent: Option<f64>,
+}
+
+/*
+    Description:
+        Watchlist as stored in a local JSON file (see read_watchlist()). Every query is scoped to netblocks
+        (ex: ["198.51.100.0/24"]) with a "net:" filter, facets is requested with every count (ex: "port,product").
+*/
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Watchlist {
+    #[serde(default)]
+    pub netblocks: Vec<String>,
+    #[serde(default)]
+    pub facets: String,
+    #[serde(default)]
+    pub entries: Vec<WatchEntry>,
+}
+
+/*
+    Description:
+        Count of one watched query at one run, as appended to the history file (one JSON object per line).
+        query is the query actually sent, netblocks included, timestamp is in seconds since the Unix epoch.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchRecord {
+    pub title: String,
+    pub query: String,
+    pub timestamp: u64,
+    pub count: u64,
+    #[serde(default)]
+    pub facets: HashMap<String, Vec<FacetValue>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum WatchAlertReason {
+    ThresholdCrossed { threshold: u64 },
+    Changed { percent: f64 },
+}
+
+/*
+    Description:
+        Raised by run_watchlist() when the count of a query crosses its threshold or changes too much.
+        previous is None on the first run of a query.
+*/
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchAlert {
+    pub title: String,
+    pub query: String,
+    pub previous: Option<u64>,
+    pub count: u64,
+    #[serde(flatten)]
+    pub reason: WatchAlertReason,
+}
+
+impl Watchlist {
+    pub fn from_saved_queries<I>(queries: I, netblocks: Vec<String>, threshold: Option<u64>, change_percent: Option<f64>) -> Self
+    where
+        I: IntoIterator<Item = SavedQuery>,
+    {
+        /*
+            Description:
+                Builds a watchlist out of saved queries (ex: collected from queries::SavedQueries), every entry getting
+                the same threshold and change_percent.
+        */
+        let entries = queries
+            .into_iter()
+            .map(|saved| WatchEntry { title: saved.title, query: saved.query, threshold, change_percent })
+            .collect();
+        Watchlist { netblocks, facets: String::new(), entries }
+    }
+
+    pub fn scoped_query(&self, query: &str) -> String {
+        /*
+            Description:
+                Returns query restricted to the netblocks of the watchlist, unchanged when there is none.
+        */
+        if self.netblocks.is_empty() {
+            query.to_string()
+        } else {
+            format!("{} net:{}", query, self.netblocks.join(","))
+        }
+    }
+}
+
+pub fn read_watchlist(path: &str) -> Result<Watchlist, ShodanError> {
+    /*
+        Description:
+            Reads a watchlist previously written by write_watchlist() (or by hand).
+        Parameters:
+            path (&str): The path of the file to read.
+        Returns:
+            Result<Watchlist, ShodanError>:
+                Ok(Watchlist): Returns the decoded watchlist.
+                Err(ShodanError): Returns an error if reading or decoding the file fails.
+    */
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub fn write_watchlist(path: &str, watchlist: &Watchlist) -> Result<(), ShodanError> {
+    /*
+        Description:
+            Writes a watchlist to a file as pretty-printed JSON.
+        Parameters:
+            path (&str): The path of the file to write.
+            watchlist (&Watchlist): The watchlist to write.
+        Returns:
+            Result<(), ShodanError>:
+                Ok(()): The file was written.
+                Err(ShodanError): Returns an error if encoding or writing the file fails.
+    */
+    let content = serde_json::to_string_pretty(watchlist)?;
+    fs::write(path, content)?;
+    Ok(())
+}
+
+pub fn read_watch_history(path: &str) -> Result<Vec<WatchRecord>, ShodanError> {
+    /*
+        Description:
+            Reads every record of a history file written by run_watchlist(), oldest first. A missing file is an empty history.
+        Parameters:
+            path (&str): The path of the history file.
+        Returns:
+            Result<Vec<WatchRecord>, ShodanError>:
+                Ok(Vec<WatchRecord>): Returns the records.
+                Err(ShodanError): Returns an error if reading the file fails or a line is not a record.
+    */
+    if !helpers::file_exists(path) {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    let mut records = Vec::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        records.push(serde_json::from_str(line)?);
+    }
+    Ok(records)
+}
+
+pub fn run_watchlist(api_key: &ApiKey, watchlist: &Watchlist, history_path: &str, limiter: &mut RateLimiter) -> Result<Vec<WatchAlert>, ShodanError> {
+    /*
+        Description:
+            Runs every query of a watchlist once with (GET /shodan/host/count), which does not use query credits,
+            appends the counts to the history file and compares them with the previous count of the same query.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            watchlist (&Watchlist): The queries to run, see read_watchlist() or Watchlist::from_saved_queries().
+            history_path (&str): The path of the history file (JSON lines), created if missing.
+            limiter (&mut RateLimiter): Rate limiter shared with the other calls of the program.
+        Returns:
+            Result<Vec<WatchAlert>, ShodanError>:
+                Ok(Vec<WatchAlert>): Returns the alerts raised by this run, empty if nothing moved.
+                Err(ShodanError): Returns an error if a count fails or the history file can't be read/ written.
+                The counts obtained before the error are kept in the history.
+    */
+    let mut previous: HashMap<String, u64> = HashMap::new();
+    for record in read_watch_history(history_path)? {
+        previous.insert(record.query, record.count);
+    }
+
+    let mut history = OpenOptions::new().create(true).append(true).open(history_path)?;
+    let mut alerts = Vec::new();
+
+    for entry in &watchlist.entries {
+        let query = watchlist.scoped_query(&entry.query);
+        limiter.wait();
+        let results: SearchResults = helpers::decode_response(shodan::get_host_count(api_key, &query, &watchlist.facets))?;
+
+        let record = WatchRecord {
+            title: entry.title.clone(),
+            query,
+            timestamp: helpers::unix_now(),
+            count: results.total,
+            facets: results.facets,
+        };
+        writeln!(history, "{}", serde_json::to_string(&record)?)?;
+
+        let last = previous.get(&record.query).copied();
+        for reason in alert_reasons(entry, last, record.count) {
+            alerts.push(WatchAlert {
+                title: record.title.clone(),
+                query: record.query.clone(),
+                previous: last,
+                count: record.count,
+                reason,
+            });
+        }
+    }
+
+    Ok(alerts)
+}
+
+pub fn watch<F>(api_key: &ApiKey, watchlist: &Watchlist, history_path: &str, interval: Duration, mut on_run: F)
+where
+    F: FnMut(Result<Vec<WatchAlert>, ShodanError>) -> bool,
+{
+    /*
+        Description:
+            Runs the watchlist every interval (ex: Duration::from_secs(6 * 3600)) with run_watchlist(), until on_run returns false.
+            A failed run is passed to on_run as an error and does not stop the schedule.
+        Parameters:
+            api_key (&ApiKey): Shodan API Key
+            watchlist (&Watchlist): The queries to run.
+            history_path (&str): The path of the history file (JSON lines), created if missing.
+            interval (Duration): Time between the start of two runs.
+            on_run (F): Receives the alerts (or the error) of every run, returns whether to keep watching.
+    */
+    let mut limiter = RateLimiter::default();
+    loop {
+        let started = Instant::now();
+        if !on_run(run_watchlist(api_key, watchlist, history_path, &mut limiter)) {
+            return;
+        }
+        if let Some(remaining) = interval.checked_sub(started.elapsed()) {
+            thread::sleep(remaining);
+        }
+    }
+}
+
+fn alert_reasons(entry: &WatchEntry, previous: Option<u64>, count: u64) -> Vec<WatchAlertReason> {
+    let mut reasons = Vec::new();
+
+    if let Some(threshold) = entry.threshold {
+        let crossed = match previous {
+            Some(previous) => (previous >= threshold) != (count >= threshold),
+            None => count >= threshold,
+        };
+        if crossed {
+            reasons.push(WatchAlertReason::ThresholdCrossed { threshold });
+        }
+    }
+
+    if let (Some(limit), Some(previous)) = (entry.change_percent, previous) {
+        let percent = if previous == 0 {
+            if count == 0 { 0.0 } else { f64::INFINITY }
+        } else {
+            (count as f64 - previous as f64) / previous as f64 * 100.0
+        };
+        if percent.abs() > limit {
+            reasons.push(WatchAlertReason::Changed { percent });
+        }
+    }
+
+    reasons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(threshold: Option<u64>, change_percent: Option<f64>) -> WatchEntry {
+        WatchEntry { title: "RDP".to_string(), query: "port:3389".to_string(), threshold, change_percent }
+    }
+
+    #[test]
+    fn threshold_alerts_when_crossed_either_way() {
+        let entry = entry(Some(10), None);
+        assert!(matches!(alert_reasons(&entry, None, 10)[..], [WatchAlertReason::ThresholdCrossed { threshold: 10 }]));
+        assert!(alert_reasons(&entry, None, 9).is_empty());
+        assert!(matches!(alert_reasons(&entry, Some(9), 10)[..], [WatchAlertReason::ThresholdCrossed { .. }]));
+        assert!(matches!(alert_reasons(&entry, Some(12), 3)[..], [WatchAlertReason::ThresholdCrossed { .. }]));
+        assert!(alert_reasons(&entry, Some(12), 15).is_empty());
+        assert!(alert_reasons(&entry, Some(3), 9).is_empty());
+    }
+
+    #[test]
+    fn change_alerts_above_the_percentage() {
+        let entry = entry(None, Some(50.0));
+        assert!(alert_reasons(&entry, None, 1000).is_empty());
+        assert!(alert_reasons(&entry, Some(100), 150).is_empty());
+        assert!(matches!(alert_reasons(&entry, Some(100), 151)[..], [WatchAlertReason::Changed { percent }] if percent == 51.0));
+        assert!(matches!(alert_reasons(&entry, Some(100), 40)[..], [WatchAlertReason::Changed { percent }] if percent == -60.0));
+        assert!(matches!(alert_reasons(&entry, Some(0), 1)[..], [WatchAlertReason::Changed { percent }] if percent.is_infinite()));
+        assert!(alert_reasons(&entry, Some(0), 0).is_empty());
+    }
+
+    #[test]
+    fn both_rules_can_alert_at_once() {
+        let entry = entry(Some(10), Some(50.0));
+        let reasons = alert_reasons(&entry, Some(4), 20);
+        assert!(matches!(reasons[..], [WatchAlertReason::ThresholdCrossed { .. }, WatchAlertReason::Changed { .. }]));
+    }
+}
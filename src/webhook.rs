@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+use reqwest::blocking::Client;
+use reqwest::blocking::Response;
+use reqwest::Error as ReqwestError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::error::ShodanError;
+use crate::helpers;
+use crate::models::AlertTrigger;
+
+// Largest delivery body accepted by WebhookReceiver::receive(), Shodan banners being far smaller
+const MAX_BODY_SIZE: usize = 10 * 1024 * 1024;
+
+// Largest request line and headers accepted by WebhookReceiver::receive()
+const MAX_HEADERS_SIZE: usize = 64 * 1024;
+
+// How long WebhookReceiver::receive() waits for a client that stopped sending
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/*
+    Description:
+        One notification as delivered by a Shodan "webhook" notifier: the HTTP headers (names lowercased) and the JSON body.
+        This is what WebhookReceiver::receive() returns and what send_test_delivery() sends, so real deliveries can be
+        recorded with write_recorded_delivery() and replayed offline later.
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedDelivery {
+    pub headers: HashMap<String, String>,
+    pub body: Value,
+}
+
+/*
+    Description:
+        Alert notification decoded from a delivery. The alert details come from the SHODAN-ALERT-* headers,
+        the body is the banner that matched the trigger, ip_str and port are pulled out of it for convenience.
+*/
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertEvent {
+    pub alert_id: Option<String>,
+    pub alert_name: Option<String>,
+    pub trigger: Option<AlertTrigger>,
+    pub signature: Option<String>,
+    pub ip_str: Option<String>,
+    pub port: Option<u16>,
+    pub banner: Value,
+}
+
+/*
+    Description:
+        Small blocking HTTP listener receiving the notifications of a "webhook" notifier during development.
+        Bind it, point a NotifierConfig::Webhook (or send_test_delivery()) to url(), then call receive() for every delivery.
+*/
+pub struct WebhookReceiver {
+    listener: TcpListener,
+}
+
+impl WebhookReceiver {
+    pub fn bind(address: &str) -> Result<Self, ShodanError> {
+        /*
+            Description:
+                Starts listening on the given address (ex: "127.0.0.1:8080", or "127.0.0.1:0" for any free port).
+        */
+        Ok(WebhookReceiver { listener: TcpListener::bind(address)? })
+    }
+
+    pub fn url(&self) -> Result<String, ShodanError> {
+        /*
+            Description:
+                Returns the URL deliveries should be sent to (ex: "http://127.0.0.1:8080/").
+        */
+        Ok(format!("http://{}/", self.listener.local_addr()?))
+    }
+
+    pub fn receive(&self) -> Result<RecordedDelivery, ShodanError> {
+        /*
+            Description:
+                Blocks until one delivery is received, answers it with "200 OK" and returns it.
+                Use decode_alert_event() to turn it into an AlertEvent.
+                A request other than POST is answered with "405 Method Not Allowed" and a body above 10 MiB with
+                "413 Payload Too Large", both being returned as an error. A client sending nothing for 30 seconds makes it
+                return an IO error.
+        */
+        let (mut stream, _) = self.listener.accept()?;
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?.take((MAX_HEADERS_SIZE + MAX_BODY_SIZE) as u64));
+
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+
+        let mut headers = HashMap::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+            }
+        }
+
+        let method = request_line.split_whitespace().next().unwrap_or_default();
+        if method != "POST" {
+            let err = ShodanError::Invalid(format!("webhook deliveries are POST requests, got \"{}\"", request_line.trim()));
+            return Err(refuse(&mut stream, "405 Method Not Allowed\r\nAllow: POST", err));
+        }
+
+        let chunked = headers
+            .get("transfer-encoding")
+            .is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked"));
+        let mut body = Vec::new();
+        if chunked {
+            loop {
+                let mut size_line = String::new();
+                reader.read_line(&mut size_line)?;
+                let size_field = size_line.trim().split(';').next().unwrap_or_default();
+                let size = usize::from_str_radix(size_field, 16)
+                    .map_err(|_| ShodanError::Invalid(format!("invalid chunk size \"{}\"", size_line.trim())))?;
+                if body.len().checked_add(size).is_none_or(|total| total > MAX_BODY_SIZE) {
+                    return Err(refuse(&mut stream, "413 Payload Too Large", body_too_large()));
+                }
+                let mut chunk = vec![0; size + 2];
+                reader.read_exact(&mut chunk)?;
+                if size == 0 {
+                    break;
+                }
+                body.extend_from_slice(&chunk[..size]);
+            }
+        } else {
+            let length = headers
+                .get("content-length")
+                .and_then(|length| length.parse::<usize>().ok())
+                .unwrap_or(0);
+            if length > MAX_BODY_SIZE {
+                return Err(refuse(&mut stream, "413 Payload Too Large", body_too_large()));
+            }
+            body.resize(length, 0);
+            reader.read_exact(&mut body)?;
+        }
+
+        stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")?;
+
+        let body = serde_json::from_slice(&body)
+            .map_err(|err| ShodanError::Invalid(format!("{} delivery body is not JSON: {}", request_line.trim(), err)))?;
+        Ok(RecordedDelivery { headers, body })
+    }
+}
+
+pub fn decode_alert_event(delivery: &RecordedDelivery) -> Result<AlertEvent, ShodanError> {
+    /*
+        Description:
+            Decodes a delivery of a "webhook" notifier into a typed alert event.
+        Parameters:
+            delivery (&RecordedDelivery): Delivery returned by WebhookReceiver::receive() or read_recorded_delivery().
+        Returns:
+            Result<AlertEvent, ShodanError>:
+                Ok(AlertEvent): Returns the decoded event.
+                Err(ShodanError): Returns ShodanError::Invalid if the body is not a banner object.
+    */
+    if !delivery.body.is_object() {
+        return Err(ShodanError::Invalid("delivery body is not a banner object".to_string()));
+    }
+
+    let header = |name: &str| delivery.headers.get(name).cloned();
+    Ok(AlertEvent {
+        alert_id: header("shodan-alert-id"),
+        alert_name: header("shodan-alert-name"),
+        trigger: header("shodan-alert-trigger").map(AlertTrigger::from),
+        signature: header("shodan-signature-sha1"),
+        ip_str: delivery.body.get("ip_str").and_then(Value::as_str).map(String::from),
+        port: delivery
+            .body
+            .get("port")
+            .and_then(Value::as_u64)
+            .and_then(|port| u16::try_from(port).ok()),
+        banner: delivery.body.clone(),
+    })
+}
+
+pub fn send_test_delivery(url: &str, delivery: &RecordedDelivery) -> Result<Response, ReqwestError> {
+    /*
+        Description:
+            Test-fire a webhook
+            Sends a delivery to a receiver the same way a Shodan "webhook" notifier does (POST with the SHODAN-ALERT-* headers
+            and the banner as JSON body), so alert handlers can be exercised offline with recorded payloads.
+        Parameters:
+            url (&str): URL of the receiver (ex: WebhookReceiver::url()).
+            delivery (&RecordedDelivery): The delivery to send.
+        Returns:
+            Result<Response, ReqwestError>:
+                Ok(Response): Returns the result of the POST request containing the response.
+                Err(ReqwestError): Returns an error if the POST request fails.
+    */
+    let client = Client::new();
+    let body = serde_json::to_string(&delivery.body).unwrap_or_else(|err| {
+        eprintln!("Error converting JSON: {}", err);
+        String::new()
+    });
+
+    let mut request = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .body(body.into_bytes());
+    for (name, value) in &delivery.headers {
+        if !matches!(name.as_str(), "content-length" | "content-type" | "host" | "transfer-encoding") {
+            request = request.header(name.as_str(), value.as_str());
+        }
+    }
+    let response = helpers::send("send_test_delivery", request)?;
+    Ok(response)
+}
+
+pub fn write_recorded_delivery(path: &str, delivery: &RecordedDelivery) -> Result<(), ShodanError> {
+    /*
+        Description:
+            Records a delivery to a file as pretty-printed JSON, to be replayed later with send_test_delivery().
+    */
+    fs::write(path, serde_json::to_string_pretty(delivery)?)?;
+    Ok(())
+}
+
+pub fn read_recorded_delivery(path: &str) -> Result<RecordedDelivery, ShodanError> {
+    /*
+        Description:
+            Reads a delivery previously recorded with write_recorded_delivery().
+    */
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+fn body_too_large() -> ShodanError {
+    ShodanError::Invalid(format!("delivery body is larger than {} bytes", MAX_BODY_SIZE))
+}
+
+fn refuse(stream: &mut TcpStream, status: &str, err: ShodanError) -> ShodanError {
+    // Answers a refused delivery before the connection is closed, the client may already be gone so a failed write is ignored
+    let _ = stream.write_all(format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status).as_bytes());
+    err
+}